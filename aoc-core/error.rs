@@ -0,0 +1,93 @@
+//!
+//! Error raised on malformed puzzle input
+//!
+
+use std::{fmt, io};
+
+/// Location of a malformed input along with what was expected there
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// Name of the file the input comes from, if any
+    pub file: Option<String>,
+    /// Line number, starting at 1
+    pub line: usize,
+    /// Column number in characters, starting at 1
+    pub column: usize,
+    /// Description of the expected token
+    pub expected: String,
+    /// Description of the token actually found
+    pub found: String,
+    /// Content of the offending line
+    pub text: String,
+}
+
+impl ParseError {
+    pub fn with_file(mut self, file: &str) -> Self {
+        self.file = Some(file.to_string());
+        self
+    }
+}
+
+/// Rendered like a compiler diagnostic, pointing at the offending column
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let number = self.line.to_string();
+        let margin = " ".repeat(number.len());
+
+        writeln!(f, "error: expected {}, found {}", self.expected, self.found)?;
+        match &self.file {
+            Some(file) => writeln!(f, "{margin}--> {file}:{}:{}", self.line, self.column)?,
+            None => writeln!(f, "{margin}--> line {}, column {}", self.line, self.column)?,
+        }
+        writeln!(f, "{margin} |")?;
+        writeln!(f, "{number} | {}", self.text)?;
+        write!(f, "{margin} | {}^", " ".repeat(self.column - 1))
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Failure to get a puzzle out of an input source
+#[derive(Debug)]
+pub enum InputError {
+    Io { name: String, error: io::Error },
+    Parse(ParseError),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Io { name, error } => write!(f, "error: cannot read {name}: {error}"),
+            InputError::Parse(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl From<ParseError> for InputError {
+    fn from(error: ParseError) -> Self {
+        InputError::Parse(error)
+    }
+}
+
+/// Diagnostic points at the offending column of the line
+#[test]
+fn display() {
+    let error = ParseError {
+        file: Some(String::from("input.txt")),
+        line: 12,
+        column: 3,
+        expected: String::from("`X`, `Y` or `Z`"),
+        found: String::from("`W`"),
+        text: String::from("A W"),
+    };
+
+    let mut expected = String::new();
+    expected.push_str("error: expected `X`, `Y` or `Z`, found `W`\n");
+    expected.push_str("  --> input.txt:12:3\n");
+    expected.push_str("   |\n");
+    expected.push_str("12 | A W\n");
+    expected.push_str("   |   ^");
+    assert_eq!(error.to_string(), expected);
+}
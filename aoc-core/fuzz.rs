@@ -0,0 +1,183 @@
+//!
+//! Mutation based fuzzing: inputs derived from a corpus are fed to the parser and solver of a day,
+//! which have to either answer or return an error, never panic
+//!
+//! The corpus of a day is made of its example inputs `testNN.txt` along with the files of its
+//! `corpus` directory, where inputs found to crash are saved to be checked from then on.
+//!
+
+use crate::{ParseError, Rng, Solver};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::Path;
+
+/// Parser of a day giving a puzzle that can be solved, like [`crate::Day::parse`]
+pub type Parser = fn(&str) -> Result<Box<dyn Solver>, ParseError>;
+
+/// Input making a day panic
+#[derive(Debug)]
+pub struct Crash {
+    pub input: Vec<u8>,
+    pub message: String,
+}
+
+/// Mutations applied to an input before feeding it
+const MAX_MUTATIONS: usize = 8;
+
+/// Bytes inputs are usually made of
+const BYTES: &[u8] = b"0123456789 \n,-[]:>$./#SEabcxyzABCXYZ";
+
+/// Tokens likely to reach edge cases: limits of integer types, separators and nesting
+const TOKENS: &[&str] = &[
+    "0",
+    "1",
+    "-1",
+    "255",
+    "65536",
+    "2147483647",
+    "4294967296",
+    "18446744073709551615",
+    "18446744073709551616",
+    "99999999",
+    "\n",
+    "\n\n",
+    " -> ",
+    "[[[[",
+    "]]]]",
+    "$ cd ..",
+    "$ cd /",
+    "dir a",
+    "old",
+    "\r\n",
+    "\u{feff}",
+    "é",
+];
+
+/// Example inputs and saved inputs of a day directory, sorted by name
+pub fn corpus(dir: &str) -> Vec<Vec<u8>> {
+    let mut paths = crate::testing::examples(dir)
+        .into_iter()
+        .map(|(input, _)| input)
+        .collect::<Vec<_>>();
+    if let Ok(entries) = std::fs::read_dir(Path::new(dir).join("corpus")) {
+        let mut saved = entries
+            .map(|entry| entry.unwrap().path())
+            .collect::<Vec<_>>();
+        saved.sort();
+        paths.extend(saved);
+    }
+    paths
+        .iter()
+        .map(|path| std::fs::read(path).unwrap())
+        .collect()
+}
+
+/// Apply a random mutation to an input: flip or replace a byte, insert bytes, tokens or a slice
+/// of another input, delete or duplicate a slice, or cut the input short
+pub fn mutate(rng: &mut Rng, input: &mut Vec<u8>, corpus: &[Vec<u8>]) {
+    let position = rng.range(0..=input.len());
+    let slice = |rng: &mut Rng, data: &[u8]| {
+        let start = rng.below(data.len());
+        start..(start + rng.range(1..=16)).min(data.len())
+    };
+    match rng.below(8) {
+        0 if !input.is_empty() => {
+            let index = rng.below(input.len());
+            input[index] ^= 1 << rng.below(8);
+        }
+        1 if !input.is_empty() => {
+            let index = rng.below(input.len());
+            input[index] = *rng.pick(BYTES);
+        }
+        2 => {
+            let token = rng.pick(TOKENS);
+            input.splice(position..position, token.bytes());
+        }
+        3 if !input.is_empty() => {
+            let range = slice(rng, input);
+            input.drain(range);
+        }
+        4 if !input.is_empty() => {
+            let range = slice(rng, input);
+            let copy = input[range].to_vec();
+            input.splice(position..position, copy);
+        }
+        5 => {
+            let other = rng.pick(corpus);
+            if !other.is_empty() {
+                let range = slice(rng, other);
+                input.splice(position..position, other[range].iter().copied());
+            }
+        }
+        6 => input.truncate(position),
+        _ => input.insert(position, *rng.pick(BYTES)),
+    }
+}
+
+/// Parse and solve an input, catching any panic. Invalid UTF-8 is replaced rather than rejected,
+/// to feed the parser with characters it does not expect
+pub fn check(parser: Parser, input: &[u8]) -> Result<(), Crash> {
+    let text = String::from_utf8_lossy(input);
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        if let Ok(puzzle) = parser(&text) {
+            // Answering may fail with an error, just not panic
+            let _ = (puzzle.part1(), puzzle.part2());
+        }
+    }));
+    result.map_err(|payload| Crash {
+        input: input.to_vec(),
+        message: panic_message(payload.as_ref()),
+    })
+}
+
+/// Check every input of the corpus, then `iterations` mutated ones, stopping at the first crash
+pub fn run(
+    parser: Parser,
+    corpus: &[Vec<u8>],
+    rng: &mut Rng,
+    iterations: u64,
+) -> Result<(), Crash> {
+    assert!(!corpus.is_empty(), "empty corpus");
+    for input in corpus {
+        check(parser, input)?;
+    }
+    for _ in 0..iterations {
+        let mut input = rng.pick(corpus).clone();
+        for _ in 0..rng.range(1..=MAX_MUTATIONS) {
+            mutate(rng, &mut input, corpus);
+        }
+        check(parser, &input)?;
+    }
+    Ok(())
+}
+
+/// Message a panic was started with
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        String::from("unknown panic")
+    }
+}
+
+#[cfg(test)]
+fn fragile(input: &str) -> Result<Box<dyn Solver>, ParseError> {
+    assert!(!input.contains('!'), "unexpected `!`");
+    Err(crate::parse::end_of_input(input, "nothing"))
+}
+
+/// Panics are caught along with the input causing them
+#[test]
+fn crash() {
+    assert!(check(fragile, b"1\n2\n").is_ok());
+    let crash = check(fragile, b"1\n!\n").unwrap_err();
+    assert_eq!(crash.input, b"1\n!\n");
+    assert_eq!(crash.message, "unexpected `!`");
+
+    // Bit flips eventually turn a space into `!`
+    let mut rng = Rng::new(0);
+    assert!(run(fragile, &[b"1 2\n".to_vec()], &mut rng, 0).is_ok());
+    assert!(run(fragile, &[b"1 2\n".to_vec()], &mut rng, 10_000).is_err());
+}
@@ -0,0 +1,286 @@
+//!
+//! Points and directions on a plane, for the ropes, maps and caves the puzzles move around in
+//!
+//! Coordinates are signed so that moving never underflows. Like on a [`Grid`](crate::Grid), `x`
+//! grows to the right and `y` grows downwards.
+//!
+
+use std::fmt::{self, Display};
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Point {
+    pub const ORIGIN: Self = Self::new(0, 0);
+
+    pub const fn new(x: i64, y: i64) -> Self {
+        Self { x, y }
+    }
+
+    /// Distance moving only along the axes
+    pub fn manhattan(self, other: Self) -> u64 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// Distance moving along the axes and the diagonals, points touching are at most 1 apart
+    pub fn chebyshev(self, other: Self) -> u64 {
+        self.x.abs_diff(other.x).max(self.y.abs_diff(other.y))
+    }
+
+    /// Sign of each coordinate, which is the step from the origin towards the point
+    pub fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+
+    /// The 4 orthogonal neighbors
+    pub fn neighbors4(self) -> impl Iterator<Item = Self> {
+        Direction::ORTHOGONAL
+            .into_iter()
+            .map(move |d| self + d.step())
+    }
+
+    /// The 8 neighbors, diagonals included
+    pub fn neighbors8(self) -> impl Iterator<Item = Self> {
+        Direction::ALL.into_iter().map(move |d| self + d.step())
+    }
+
+    /// Position on a grid, if neither coordinate is negative
+    pub fn position(self) -> Option<(usize, usize)> {
+        (self.x >= 0 && self.y >= 0).then_some((self.x as usize, self.y as usize))
+    }
+}
+
+impl From<(usize, usize)> for Point {
+    fn from((x, y): (usize, usize)) -> Self {
+        Self::new(x as i64, y as i64)
+    }
+}
+
+impl Add for Point {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl AddAssign for Point {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Point {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl SubAssign for Point {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Neg for Point {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl Mul<i64> for Point {
+    type Output = Self;
+
+    fn mul(self, factor: i64) -> Self {
+        Self::new(self.x * factor, self.y * factor)
+    }
+}
+
+/// Points are written like in the inputs, `x,y`
+impl Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction {
+    /// The 4 directions along the axes, clockwise from up
+    pub const ORTHOGONAL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    /// The 8 directions, diagonals included, clockwise from up left
+    pub const ALL: [Self; 8] = [
+        Self::UpLeft,
+        Self::Up,
+        Self::UpRight,
+        Self::Right,
+        Self::DownRight,
+        Self::Down,
+        Self::DownLeft,
+        Self::Left,
+    ];
+
+    /// Direction of a `U`, `D`, `L` or `R` letter
+    pub fn from_letter(letter: char) -> Option<Self> {
+        match letter {
+            'U' => Some(Self::Up),
+            'D' => Some(Self::Down),
+            'L' => Some(Self::Left),
+            'R' => Some(Self::Right),
+            _ => None,
+        }
+    }
+
+    /// Move of one step in this direction
+    pub const fn step(self) -> Point {
+        match self {
+            Self::Up => Point::new(0, -1),
+            Self::UpRight => Point::new(1, -1),
+            Self::Right => Point::new(1, 0),
+            Self::DownRight => Point::new(1, 1),
+            Self::Down => Point::new(0, 1),
+            Self::DownLeft => Point::new(-1, 1),
+            Self::Left => Point::new(-1, 0),
+            Self::UpLeft => Point::new(-1, -1),
+        }
+    }
+}
+
+/// Smallest rectangle holding some points, both corners included
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Bounds {
+    /// Bounds of some points, `None` without any
+    pub fn of(points: impl IntoIterator<Item = Point>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+        let mut bounds = Self {
+            min: first,
+            max: first,
+        };
+        points.for_each(|point| bounds.include(point));
+        Some(bounds)
+    }
+
+    /// Grow the bounds to hold a point
+    pub fn include(&mut self, point: Point) {
+        self.min = Point::new(self.min.x.min(point.x), self.min.y.min(point.y));
+        self.max = Point::new(self.max.x.max(point.x), self.max.y.max(point.y));
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        (self.min.x..=self.max.x).contains(&point.x) && (self.min.y..=self.max.y).contains(&point.y)
+    }
+
+    pub fn width(&self) -> u64 {
+        self.max.x.abs_diff(self.min.x) + 1
+    }
+
+    pub fn height(&self) -> u64 {
+        self.max.y.abs_diff(self.min.y) + 1
+    }
+}
+
+/// Points of the segment between two points, both ends included, as drawn by Bresenham's
+/// algorithm: each step moves to a neighbor, diagonals included
+pub fn line(from: Point, to: Point) -> impl Iterator<Item = Point> {
+    let step = (to - from).signum();
+    let (dx, dy) = ((to.x - from.x).abs(), -(to.y - from.y).abs());
+    let mut error = dx + dy;
+    let mut next = Some(from);
+    std::iter::from_fn(move || {
+        let point = next?;
+        next = (point != to).then(|| {
+            let (mut moved, doubled) = (point, 2 * error);
+            if doubled >= dy {
+                error += dy;
+                moved.x += step.x;
+            }
+            if doubled <= dx {
+                error += dx;
+                moved.y += step.y;
+            }
+            moved
+        });
+        Some(point)
+    })
+}
+
+/// Distances, neighbors and directions agree with each other
+#[test]
+fn points() {
+    let (a, b) = (Point::new(1, -2), Point::new(-3, 1));
+    assert_eq!((a.manhattan(b), a.chebyshev(b)), (7, 4));
+    assert_eq!((b - a).signum(), Point::new(-1, 1));
+    assert_eq!(a + Direction::Up.step() * 2, Point::new(1, -4));
+    assert_eq!(-a, Point::new(-1, 2));
+    assert!(Point::ORIGIN
+        .neighbors4()
+        .all(|p| p.manhattan(Point::ORIGIN) == 1));
+    assert!(Point::ORIGIN
+        .neighbors8()
+        .all(|p| p.chebyshev(Point::ORIGIN) == 1));
+    assert_eq!(Bounds::of(Point::ORIGIN.neighbors8()).unwrap().width(), 3);
+    assert_eq!(Direction::from_letter('L'), Some(Direction::Left));
+    assert_eq!(a.position(), None);
+    assert_eq!(Point::from((4, 2)).position(), Some((4, 2)));
+    assert_eq!(a.to_string(), "1,-2");
+}
+
+/// Bounds hold every point they were built from
+#[test]
+fn bounds() {
+    let bounds = Bounds::of([Point::new(2, 5), Point::new(-1, 7), Point::new(0, 6)]).unwrap();
+    assert_eq!(
+        (bounds.min, bounds.max),
+        (Point::new(-1, 5), Point::new(2, 7))
+    );
+    assert_eq!((bounds.width(), bounds.height()), (4, 3));
+    assert!(bounds.contains(Point::new(0, 5)));
+    assert!(!bounds.contains(Point::new(0, 8)));
+    assert_eq!(Bounds::of([]), None);
+}
+
+/// Lines join their ends with one step to a neighbor at a time
+#[test]
+fn lines() {
+    let points = |from, to| line(from, to).collect::<Vec<_>>();
+    assert_eq!(
+        points(Point::new(498, 4), Point::new(498, 6)),
+        [Point::new(498, 4), Point::new(498, 5), Point::new(498, 6)]
+    );
+    assert_eq!(
+        points(Point::new(2, 2), Point::new(0, 0)),
+        [Point::new(2, 2), Point::new(1, 1), Point::new(0, 0)]
+    );
+    assert_eq!(points(Point::ORIGIN, Point::ORIGIN), [Point::ORIGIN]);
+    for to in [Point::new(7, -3), Point::new(-2, 9), Point::new(-5, 0)] {
+        let line = points(Point::new(1, 1), to);
+        assert_eq!(line.len() as u64, to.chebyshev(Point::new(1, 1)) + 1);
+        assert_eq!(line.last(), Some(&to));
+        assert!(line.windows(2).all(|pair| pair[0].chebyshev(pair[1]) == 1));
+    }
+}
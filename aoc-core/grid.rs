@@ -0,0 +1,318 @@
+//!
+//! Rectangular grid of cells stored row after row, like the maps of tree heights or elevations
+//!
+//! Positions are `(x, y)` pairs: the column first, then the row counted from the top. Cells can
+//! also be reached from a [`Point`], which is outside of the grid when negative.
+//!
+
+use crate::geometry::{Direction, Point};
+use crate::parse::{self, Issues};
+use crate::ParseError;
+use std::fmt::{self, Display};
+use std::ops::{Index, IndexMut};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T: Clone> Grid<T> {
+    /// Grid with every cell set to the same value
+    pub fn new(width: usize, height: usize, value: T) -> Self {
+        Self::from_cells(width, vec![value; width * height])
+    }
+}
+
+impl<T> Grid<T> {
+    /// Grid made of cells given row after row, their count must be a multiple of the width
+    pub fn from_cells(width: usize, cells: Vec<T>) -> Self {
+        assert!(width != 0 || cells.is_empty(), "cells without width");
+        let height = cells.len().checked_div(width).unwrap_or(0);
+        assert_eq!(cells.len(), width * height, "incomplete row");
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Grid with each cell computed from its position
+    pub fn from_fn(width: usize, height: usize, cell: impl FnMut((usize, usize)) -> T) -> Self {
+        let positions = (0..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+        Self::from_cells(width, positions.map(cell).collect())
+    }
+
+    /// Parse a map with one character per cell, every row having the same width. `cell` turns a
+    /// character at a position into a cell, or gives `None` to report it as not `expected`
+    pub fn parse(
+        input: &str,
+        expected: &str,
+        cell: impl FnMut((usize, usize), char) -> Option<T>,
+    ) -> Result<Self, ParseError>
+    where
+        T: Default,
+    {
+        let mut issues = Issues::default();
+        let grid = Self::parse_checked(input, expected, cell, &mut issues);
+        issues.first(grid)
+    }
+
+    /// Parse a map like [`Grid::parse`], going on after any cell or row in error
+    pub fn parse_checked(
+        input: &str,
+        expected: &str,
+        mut cell: impl FnMut((usize, usize), char) -> Option<T>,
+        issues: &mut Issues,
+    ) -> Option<Self>
+    where
+        T: Default,
+    {
+        let lines = parse::lines(input);
+        if lines.is_empty() {
+            issues.push(parse::end_of_input(input, expected));
+            return None;
+        }
+
+        let width = lines[0].text.chars().count();
+        if width == 0 {
+            issues.push(lines[0].scan().error(expected));
+            return None;
+        }
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for (y, line) in lines.iter().enumerate() {
+            let mut scanner = line.scan();
+            for x in 0..width {
+                let Some(c) = scanner.peek() else {
+                    // A short row is reported once, its missing cells are left to their default
+                    issues.push(scanner.error(expected));
+                    cells.resize_with(width * (y + 1), T::default);
+                    break;
+                };
+                match cell((x, y), c) {
+                    Some(value) => cells.push(value),
+                    None => {
+                        issues.push(scanner.error(expected));
+                        cells.push(T::default());
+                    }
+                }
+                scanner.next_char();
+            }
+            issues.check(scanner.end());
+        }
+        Some(Self::from_cells(width, cells))
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn contains(&self, (x, y): (usize, usize)) -> bool {
+        x < self.width && y < self.height
+    }
+
+    pub fn get(&self, position: (usize, usize)) -> Option<&T> {
+        self.contains(position)
+            .then(|| &self.cells[position.1 * self.width + position.0])
+    }
+
+    pub fn get_mut(&mut self, position: (usize, usize)) -> Option<&mut T> {
+        self.contains(position)
+            .then(|| &mut self.cells[position.1 * self.width + position.0])
+    }
+
+    /// Cell at a point, `None` outside of the grid
+    pub fn at(&self, point: Point) -> Option<&T> {
+        self.get(point.position()?)
+    }
+
+    pub fn at_mut(&mut self, point: Point) -> Option<&mut T> {
+        self.get_mut(point.position()?)
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    /// Cells of a column, from top to bottom
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        assert!(x < self.width, "column out of the grid");
+        self.cells[x..].iter().step_by(self.width)
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    /// Every position, row after row
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// Every position along with its cell, row after row
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .map(move |(index, cell)| ((index % width, index / width), cell))
+    }
+
+    /// Orthogonal neighbors of a position inside the grid
+    pub fn neighbors4(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        self.around(position, &Direction::ORTHOGONAL)
+    }
+
+    /// Neighbors of a position inside the grid, diagonals included
+    pub fn neighbors8(&self, position: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        self.around(position, &Direction::ALL)
+    }
+
+    fn around(
+        &self,
+        position: (usize, usize),
+        directions: &'static [Direction],
+    ) -> impl Iterator<Item = (usize, usize)> {
+        let (width, height) = (self.width, self.height);
+        directions.iter().filter_map(move |direction| {
+            let (x, y) = (Point::from(position) + direction.step()).position()?;
+            (x < width && y < height).then_some((x, y))
+        })
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): (usize, usize)) -> &T {
+        assert!(
+            x < self.width && y < self.height,
+            "position out of the grid"
+        );
+        &self.cells[y * self.width + x]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+        assert!(
+            x < self.width && y < self.height,
+            "position out of the grid"
+        );
+        &mut self.cells[y * self.width + x]
+    }
+}
+
+impl<T> Index<Point> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, point: Point) -> &T {
+        &self[point.position().expect("position out of the grid")]
+    }
+}
+
+impl<T> IndexMut<Point> for Grid<T> {
+    fn index_mut(&mut self, point: Point) -> &mut T {
+        &mut self[point.position().expect("position out of the grid")]
+    }
+}
+
+/// Cells are written one after the other, with a line per row
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.rows() {
+            for cell in row {
+                write!(f, "{cell}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// Cells are laid out row after row and out of the grid positions are rejected
+#[test]
+fn layout() {
+    let mut grid = Grid::from_cells(3, vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!((grid.width(), grid.height()), (3, 2));
+    assert_eq!(grid[(2, 0)], 3);
+    assert_eq!(grid.get((0, 1)), Some(&4));
+    assert_eq!(grid.get((3, 0)), None);
+    assert_eq!(grid.get((0, 2)), None);
+    assert_eq!(grid[Point::new(1, 0)], 2);
+    assert_eq!(grid.at(Point::new(-1, 0)), None);
+    assert_eq!(grid.row(1), [4, 5, 6]);
+    assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), [2, 5]);
+    assert_eq!(grid.column(2).rev().copied().collect::<Vec<_>>(), [6, 3]);
+
+    grid[(1, 1)] = 0;
+    grid.row_mut(0).fill(7);
+    assert_eq!(grid.to_string(), "777\n406\n");
+    assert_eq!(Grid::new(2, 1, '.').to_string(), "..\n");
+    assert_eq!(Grid::new(3, 5, 1).cells, [1; 15]);
+    assert_eq!(Grid::new(0, 5, 1).height(), 0);
+    assert_eq!(
+        Grid::from_fn(2, 2, |(x, y)| x + 2 * y).to_string(),
+        "01\n23\n"
+    );
+}
+
+/// Neighbors stay inside the grid
+#[test]
+fn neighbors() {
+    let grid = Grid::new(3, 3, 0);
+    assert_eq!(grid.neighbors4((1, 1)).count(), 4);
+    assert_eq!(grid.neighbors8((1, 1)).count(), 8);
+    assert_eq!(
+        grid.neighbors4((0, 0)).collect::<Vec<_>>(),
+        [(1, 0), (0, 1)]
+    );
+    assert_eq!(
+        grid.neighbors8((2, 2)).collect::<Vec<_>>(),
+        [(1, 1), (2, 1), (1, 2)]
+    );
+}
+
+/// Maps are parsed with their rows of the same width
+#[test]
+fn parsed() {
+    let digit = |_, c: char| c.to_digit(10);
+    let grid = Grid::parse("12\n34\n", "a digit", digit).unwrap();
+    assert_eq!(grid.iter().last(), Some(((1, 1), &4)));
+
+    let error = Grid::parse("12\n3\n", "a digit", digit).unwrap_err();
+    assert_eq!((error.line, error.column), (2, 2));
+    let error = Grid::parse("12\n345\n", "a digit", digit).unwrap_err();
+    assert_eq!((error.line, error.column), (2, 3));
+    let error = Grid::parse("12\n3x\n", "a digit", digit).unwrap_err();
+    assert_eq!((error.line, error.column), (2, 2));
+    assert!(Grid::parse("", "a digit", digit).is_err());
+    assert!(Grid::parse("\n1\n", "a digit", digit).is_err());
+}
+
+/// Every cell and row in error is reported, the grid keeping its size
+#[test]
+fn checked() {
+    let digit = |_, c: char| c.to_digit(10);
+    let mut issues = Issues::default();
+    let grid = Grid::parse_checked("1x3\n4\n7899\n", "a digit", digit, &mut issues).unwrap();
+    assert_eq!((grid.width(), grid.height()), (3, 3));
+    let errors = issues.errors();
+    assert_eq!(
+        errors
+            .iter()
+            .map(|e| (e.line, e.column))
+            .collect::<Vec<_>>(),
+        [(1, 2), (2, 2), (3, 4)]
+    );
+}
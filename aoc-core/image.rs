@@ -0,0 +1,137 @@
+//!
+//! Pictures of grids in the portable pixmap formats, to look at the state a solution went through
+//!
+//! Binary PPM keeps the colors and binary PGM their brightness only. Both are read by most image
+//! viewers and need nothing but a short header in front of the pixels.
+//!
+
+use crate::Grid;
+
+/// Red, green and blue levels of a pixel
+pub type Rgb = [u8; 3];
+
+pub const BLACK: Rgb = [0, 0, 0];
+
+pub const WHITE: Rgb = [255, 255, 255];
+
+/// Dark blue behind text drawings
+const BACKGROUND: Rgb = [25, 25, 35];
+
+/// Colors `gradient` goes through, from cold to hot
+const GRADIENT: [Rgb; 5] = [
+    [20, 20, 140],
+    [0, 150, 230],
+    [40, 190, 60],
+    [250, 210, 20],
+    [210, 30, 20],
+];
+
+/// Gray of a level between 0 and `max`, from black to white
+pub fn gray(level: u64, max: u64) -> Rgb {
+    let level = (level.min(max) * 255).checked_div(max).unwrap_or(255) as u8;
+    [level; 3]
+}
+
+/// Color of a level between 0 and `max` on a scale from blue to red, for distances or heights
+pub fn gradient(level: u64, max: u64) -> Rgb {
+    let scaled = level.min(max) as f64 / max.max(1) as f64 * (GRADIENT.len() - 1) as f64;
+    let index = (scaled as usize).min(GRADIENT.len() - 2);
+    let (from, to) = (GRADIENT[index], GRADIENT[index + 1]);
+    let fraction = scaled - index as f64;
+    std::array::from_fn(|channel| {
+        let (from, to) = (f64::from(from[channel]), f64::from(to[channel]));
+        (from + (to - from) * fraction).round() as u8
+    })
+}
+
+/// Picture of a text drawing with a pixel per character: blanks and dots are dark, `#` is light
+/// and every other character has a color of its own. Short lines are padded with blanks
+pub fn text(drawing: &str) -> Grid<Rgb> {
+    let lines = drawing.lines().collect::<Vec<_>>();
+    let width = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut picture = Grid::new(width, lines.len(), BACKGROUND);
+    for (y, line) in lines.iter().enumerate() {
+        for (x, c) in line.chars().enumerate() {
+            picture[(x, y)] = match c {
+                ' ' | '.' => BACKGROUND,
+                '#' => [200, 200, 190],
+                c => gradient(u64::from(c) * 7 % 32, 31),
+            };
+        }
+    }
+    picture
+}
+
+/// Picture with each pixel turned into a square of `factor` pixels
+pub fn scaled(picture: &Grid<Rgb>, factor: usize) -> Grid<Rgb> {
+    Grid::from_fn(
+        picture.width() * factor,
+        picture.height() * factor,
+        |(x, y)| picture[(x / factor, y / factor)],
+    )
+}
+
+/// Binary PPM of a picture
+pub fn ppm(picture: &Grid<Rgb>) -> Vec<u8> {
+    let mut data = header("P6", picture);
+    for row in picture.rows() {
+        data.extend(row.iter().flatten());
+    }
+    data
+}
+
+/// Binary PGM of a picture, colors are turned to their brightness
+pub fn pgm(picture: &Grid<Rgb>) -> Vec<u8> {
+    let mut data = header("P5", picture);
+    for row in picture.rows() {
+        data.extend(row.iter().map(|&[r, g, b]| {
+            ((299 * u32::from(r) + 587 * u32::from(g) + 114 * u32::from(b)) / 1000) as u8
+        }));
+    }
+    data
+}
+
+fn header(magic: &str, picture: &Grid<Rgb>) -> Vec<u8> {
+    format!("{magic}\n{} {}\n255\n", picture.width(), picture.height()).into_bytes()
+}
+
+/// Pixels follow a header with the size of the picture
+#[test]
+fn encoded() {
+    let picture = Grid::from_cells(2, vec![BLACK, WHITE, [255, 0, 0], [0, 0, 255]]);
+    let mut expected = b"P6\n2 2\n255\n".to_vec();
+    expected.extend([0, 0, 0, 255, 255, 255, 255, 0, 0, 0, 0, 255]);
+    assert_eq!(ppm(&picture), expected);
+    let mut expected = b"P5\n2 2\n255\n".to_vec();
+    expected.extend([0, 255, 76, 29]);
+    assert_eq!(pgm(&picture), expected);
+}
+
+/// Scales go from their first color to their last one
+#[test]
+fn scales() {
+    assert_eq!(gray(0, 9), BLACK);
+    assert_eq!(gray(9, 9), WHITE);
+    assert_eq!(gray(3, 0), WHITE);
+    assert_eq!(gradient(0, 100), GRADIENT[0]);
+    assert_eq!(gradient(50, 100), GRADIENT[2]);
+    assert_eq!(gradient(200, 100), GRADIENT[4]);
+}
+
+/// Text drawings have a pixel per character and scaling keeps their layout
+#[test]
+fn drawn() {
+    let picture = text("#.\n#\nab\n");
+    assert_eq!((picture.width(), picture.height()), (2, 3));
+    assert_eq!(picture[(1, 1)], BACKGROUND);
+    assert_eq!(picture[(0, 0)], picture[(0, 1)]);
+    assert_ne!(picture[(0, 2)], picture[(1, 2)]);
+
+    let large = scaled(&picture, 3);
+    assert_eq!((large.width(), large.height()), (6, 9));
+    assert_eq!(large[(5, 8)], picture[(1, 2)]);
+}
@@ -0,0 +1,84 @@
+//!
+//! Reading puzzle inputs from files or from the standard input
+//!
+
+use crate::InputError;
+use std::io;
+
+/// Path standing for the standard input
+pub const STDIN: &str = "-";
+
+/// Name of an input as displayed in diagnostics
+pub fn name(path: &str) -> &str {
+    if path == STDIN {
+        "<stdin>"
+    } else {
+        path
+    }
+}
+
+/// Read a whole input, from a file or from the standard input if `path` is `-`
+pub fn read(path: &str) -> Result<String, InputError> {
+    let result = if path == STDIN {
+        io::read_to_string(io::stdin())
+    } else {
+        std::fs::read_to_string(path)
+    };
+    result.map_err(|error| InputError::Io {
+        name: name(path).to_string(),
+        error,
+    })
+}
+
+/// Normalize an input so that parsers only deal with one layout: no byte order mark, `\n` line
+/// endings, no trailing blank line and a final newline
+pub fn normalize(input: &str) -> String {
+    let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+
+    let mut lines = input
+        .lines()
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect::<Vec<_>>();
+    while lines.last().is_some_and(|line| line.trim().is_empty()) {
+        lines.pop();
+    }
+
+    let mut result = String::with_capacity(input.len() + 1);
+    for line in lines {
+        result.push_str(line);
+        result.push('\n');
+    }
+    result
+}
+
+/// Missing files are reported with their name
+#[test]
+fn missing() {
+    let error = read("missing.txt").unwrap_err();
+    assert!(error
+        .to_string()
+        .starts_with("error: cannot read missing.txt: "));
+}
+
+/// Every layout variant is normalized to the same input
+#[test]
+fn layouts() {
+    let expected = "1\n2\n\n3 \n";
+    assert_eq!(normalize("1\n2\n\n3 \n"), expected);
+    assert_eq!(normalize("1\n2\n\n3 "), expected);
+    assert_eq!(normalize("1\r\n2\r\n\r\n3 \r\n"), expected);
+    assert_eq!(normalize("1\r\n2\r\n\r\n3 "), expected);
+    assert_eq!(normalize("1\n2\n\n3 \n\n\n"), expected);
+    assert_eq!(normalize("1\n2\n\n3 \n  \n\t\n"), expected);
+    assert_eq!(normalize("1\r\n2\r\n\r\n3 \r\n\r\n"), expected);
+    assert_eq!(normalize("\u{feff}1\n2\n\n3 \n"), expected);
+    assert_eq!(normalize("\u{feff}1\r\n2\r\n\r\n3 \r"), expected);
+}
+
+/// Blank inputs are empty once normalized
+#[test]
+fn blank() {
+    assert_eq!(normalize(""), "");
+    assert_eq!(normalize("\n\n"), "");
+    assert_eq!(normalize("\u{feff}\r\n"), "");
+}
@@ -0,0 +1,96 @@
+//!
+//! Minimal JSON writer, enough to emit flat records one per line
+//!
+
+use std::fmt::{Display, Write};
+
+/// JSON string literal of a text, with quotes and control characters escaped
+pub fn string(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 2);
+    result.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            c if c.is_control() => write!(result, "\\u{:04x}", u32::from(c)).unwrap(),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// JSON object written on a single line, fields are kept in insertion order
+pub struct Object {
+    buffer: String,
+}
+
+impl Object {
+    pub fn new() -> Self {
+        Self {
+            buffer: String::from("{"),
+        }
+    }
+
+    fn key(&mut self, key: &str) {
+        if self.buffer.len() > 1 {
+            self.buffer.push(',');
+        }
+        self.buffer.push_str(&string(key));
+        self.buffer.push(':');
+    }
+
+    pub fn string(mut self, key: &str, value: &str) -> Self {
+        self.key(key);
+        self.buffer.push_str(&string(value));
+        self
+    }
+
+    /// Field with a numeric or boolean value, written as is
+    pub fn value(mut self, key: &str, value: impl Display) -> Self {
+        self.key(key);
+        write!(self.buffer, "{value}").unwrap();
+        self
+    }
+
+    /// Field with a value that is already JSON, like a nested object
+    pub fn raw(mut self, key: &str, json: &str) -> Self {
+        self.key(key);
+        self.buffer.push_str(json);
+        self
+    }
+
+    pub fn finish(mut self) -> String {
+        self.buffer.push('}');
+        self.buffer
+    }
+}
+
+impl Default for Object {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Special characters are escaped
+#[test]
+fn escape() {
+    assert_eq!(string("#.\n.#\n"), r##""#.\n.#\n""##);
+    assert_eq!(string(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+    assert_eq!(string("\u{1}\t"), r#""\u0001\t""#);
+}
+
+/// Fields are separated by commas
+#[test]
+fn object() {
+    let object = Object::new()
+        .value("day", 10)
+        .string("answer", "#.\n")
+        .raw("nested", "{}")
+        .finish();
+    assert_eq!(object, r##"{"day":10,"answer":"#.\n","nested":{}}"##);
+    assert_eq!(Object::new().finish(), "{}");
+}
@@ -0,0 +1,330 @@
+//!
+//! Building blocks shared by every day of the puzzle
+//!
+
+pub mod error;
+pub mod fuzz;
+pub mod geometry;
+pub mod grid;
+pub mod image;
+pub mod input;
+pub mod json;
+pub mod observe;
+pub mod params;
+pub mod parse;
+pub mod rng;
+pub mod shrink;
+pub mod testing;
+
+pub use error::{InputError, ParseError};
+pub use geometry::{Bounds, Direction, Point};
+pub use grid::Grid;
+pub use observe::Observer;
+pub use params::Params;
+pub use rng::Rng;
+
+use std::io::Read;
+
+/// A puzzle solution: the input is parsed once and both parts are answered from it
+pub trait Solution: Sized {
+    /// Answer of the first part
+    type Part1: Answer;
+
+    /// Answer of the second part
+    type Part2: Answer;
+
+    /// Build the puzzle from a normalized input, see [`input::normalize`]
+    fn parse(input: &str) -> Result<Self, ParseError>;
+
+    fn part1(&self) -> Self::Part1;
+
+    fn part2(&self) -> Self::Part2;
+
+    /// Build the puzzle from a raw input, whatever its line endings
+    fn from_input(input: &str) -> Result<Self, ParseError> {
+        Self::parse(&input::normalize(input))
+    }
+
+    /// Build the puzzle from the whole content of a reader
+    fn from_reader<R: Read>(mut reader: R) -> Result<Self, InputError> {
+        let mut data = String::new();
+        reader
+            .read_to_string(&mut data)
+            .map_err(|error| InputError::Io {
+                name: String::from("reader"),
+                error,
+            })?;
+        Ok(Self::from_input(&data)?)
+    }
+
+    /// Build the puzzle from the content of a file, or of the standard input if `path` is `-`
+    fn from_file(path: &str) -> Result<Self, InputError> {
+        let data = input::read(path)?;
+        Self::from_input(&data).map_err(|e| InputError::Parse(e.with_file(input::name(path))))
+    }
+}
+
+/// Answer of a part, or the error preventing a puzzle that parsed from being answered, like a value
+/// overflowing on the way
+pub trait Answer {
+    /// Answer as text, multi-line for a picture
+    fn text(self) -> Result<String, ParseError>;
+}
+
+macro_rules! answers {
+    ($($answer:ty),*) => {
+        $(
+            impl Answer for $answer {
+                fn text(self) -> Result<String, ParseError> {
+                    Ok(self.to_string())
+                }
+            }
+        )*
+    };
+}
+
+answers!(i64, u32, u64, u128, usize, String);
+
+/// The error points at the part of the input that cannot be answered
+impl<T: Answer> Answer for Result<T, ParseError> {
+    fn text(self) -> Result<String, ParseError> {
+        self?.text()
+    }
+}
+
+/// Generator of random valid inputs, to stress the parsers and benchmark on large inputs
+pub trait Generate {
+    /// Input made of about `size` top level items (elves, rows, monkeys, ...) with `size` at least 1
+    fn generate(rng: &mut Rng, size: usize) -> String;
+}
+
+/// Slow but straightforward solution of a puzzle, answers of the optimized one are checked against
+/// it on generated inputs
+pub trait Reference: Solution {
+    fn reference_part1(&self) -> Self::Part1;
+
+    fn reference_part2(&self) -> Self::Part2;
+}
+
+/// Picture of a puzzle as solved, to see where a wrong answer comes from
+pub trait Render: Solution {
+    fn render(&self) -> Grid<image::Rgb>;
+}
+
+/// Solution telling how its answers were found, to review them rather than trust a bare number
+pub trait Explain: Solution {
+    /// Lines telling what the answer of a part is made of, like the items or the path it counts
+    fn explain(&self, part: u32) -> String;
+}
+
+/// Solution showing each of its steps to an observer, see [`observe`]
+pub trait Observe: Solution {
+    /// Answer of a part of a raw input, telling the observer about every step made to find it
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError>;
+}
+
+/// Solution whose constants can be overridden, see [`params`]
+pub trait Configure: Solution {
+    /// Constants of the puzzle, the default ones being those of the puzzle
+    type Params: Params + Default + Clone + 'static;
+
+    /// Build the puzzle from a normalized input, to be solved with `params`
+    fn parse_with(input: &str, params: Self::Params) -> Result<Self, ParseError>;
+
+    /// Build the puzzle from a raw input, to be solved with `params`
+    fn from_input_with(input: &str, params: Self::Params) -> Result<Self, ParseError> {
+        Self::parse_with(&input::normalize(input), params)
+    }
+}
+
+/// Solution whose parser goes on after the errors it can recover from, see [`parse::Issues`]
+pub trait Lint: Solution {
+    /// Every error of a normalized input, none if it parses
+    fn lint(input: &str) -> Vec<ParseError>;
+}
+
+/// Object safe view of a parsed puzzle, answers are rendered as text
+pub trait Solver {
+    fn part1(&self) -> Result<String, ParseError>;
+
+    fn part2(&self) -> Result<String, ParseError>;
+}
+
+impl<S: Solution> Solver for S {
+    fn part1(&self) -> Result<String, ParseError> {
+        Solution::part1(self).text()
+    }
+
+    fn part2(&self) -> Result<String, ParseError> {
+        Solution::part2(self).text()
+    }
+}
+
+/// A day of the puzzle, usable by tools that do not know its solution type
+#[derive(Clone, Copy)]
+pub struct Day {
+    pub number: u32,
+    pub title: &'static str,
+    pub parse: fn(&str) -> Result<Box<dyn Solver>, ParseError>,
+    pub generate: fn(&mut Rng, usize) -> String,
+    /// Parse an input and render it, for the days implementing [`Render`]
+    pub render: Option<RenderFn>,
+    /// Solve a part of an input step by step, for the days implementing [`Observe`]
+    pub observe: Option<ObserveFn>,
+    /// Parse an input and explain the answer of a part, for the days implementing [`Explain`]
+    pub explain: Option<ExplainFn>,
+    /// Override the constants of the puzzle, for the days implementing [`Configure`]
+    pub params: Option<ParamsFn>,
+    /// Every error of an input, only the first one unless the day implements [`Lint`]
+    pub lint: LintFn,
+}
+
+/// Picture of an input once parsed and solved
+pub type RenderFn = fn(&str) -> Result<Grid<image::Rgb>, ParseError>;
+
+/// Explanation of the answer of a part of an input once parsed
+pub type ExplainFn = fn(&str, u32) -> Result<String, ParseError>;
+
+/// Answer of a part of an input, solved under an observer
+pub type ObserveFn = fn(&str, u32, &mut dyn Observer) -> Result<String, ParseError>;
+
+/// Errors of a raw input, in their order in the input
+pub type LintFn = fn(&str) -> Vec<ParseError>;
+
+/// Parser solving with the default parameters overridden by settings like `rounds=50`
+pub type ParamsFn = fn(&[String]) -> Result<Configured, String>;
+
+/// Parser of a day along with the parameters it solves the puzzle with
+pub struct Configured {
+    /// Name and value of every parameter of the day
+    pub values: Vec<(&'static str, String)>,
+    parse: Box<ConfiguredParse>,
+}
+
+/// Parser capturing the parameters of a day
+type ConfiguredParse = dyn Fn(&str) -> Result<Box<dyn Solver>, ParseError>;
+
+impl Configured {
+    /// Build the puzzle from a raw input
+    pub fn parse(&self, input: &str) -> Result<Box<dyn Solver>, ParseError> {
+        (self.parse)(input)
+    }
+}
+
+impl Day {
+    pub const fn new<S: Solution + Generate + 'static>(number: u32, title: &'static str) -> Self {
+        Self {
+            number,
+            title,
+            parse: parse_boxed::<S>,
+            generate: S::generate,
+            render: None,
+            observe: None,
+            explain: None,
+            params: None,
+            lint: lint_first::<S>,
+        }
+    }
+
+    /// The same day, rendered by `S`
+    pub const fn with_render<S: Render + 'static>(self) -> Self {
+        Self {
+            render: Some(render_parsed::<S>),
+            ..self
+        }
+    }
+
+    /// The same day, observed through `S`
+    pub const fn with_observe<S: Observe + 'static>(self) -> Self {
+        Self {
+            observe: Some(S::observe),
+            ..self
+        }
+    }
+
+    /// The same day, explained by `S`
+    pub const fn with_explain<S: Explain + 'static>(self) -> Self {
+        Self {
+            explain: Some(explain_parsed::<S>),
+            ..self
+        }
+    }
+
+    /// The same day, linted by `S`
+    pub const fn with_lint<S: Lint + 'static>(self) -> Self {
+        Self {
+            lint: lint_all::<S>,
+            ..self
+        }
+    }
+
+    /// The same day, with the parameters of `S`
+    pub const fn with_params<S: Configure + 'static>(self) -> Self {
+        Self {
+            params: Some(configure_boxed::<S>),
+            ..self
+        }
+    }
+
+    /// Parser solving with the default parameters of the day overridden by `settings`, a day
+    /// without parameters cannot have any setting
+    pub fn configure(&self, settings: &[String]) -> Result<Configured, String> {
+        match self.params {
+            Some(params) => params(settings),
+            None if settings.is_empty() => Ok(Configured {
+                values: Vec::new(),
+                parse: Box::new(self.parse),
+            }),
+            None => Err(format!("day {} has no parameters", self.number)),
+        }
+    }
+}
+
+fn configure_boxed<S: Configure + 'static>(settings: &[String]) -> Result<Configured, String> {
+    let mut params = S::Params::default();
+    params::apply(&mut params, settings)?;
+    Ok(Configured {
+        values: params::values(&mut params),
+        parse: Box::new(move |input| Ok(Box::new(S::from_input_with(input, params.clone())?))),
+    })
+}
+
+fn lint_first<S: Solution>(input: &str) -> Vec<ParseError> {
+    S::from_input(input).err().into_iter().collect()
+}
+
+fn lint_all<S: Lint>(input: &str) -> Vec<ParseError> {
+    let mut errors = S::lint(&input::normalize(input));
+    errors.sort_by_key(|error| (error.line, error.column));
+    errors
+}
+
+fn render_parsed<S: Render>(input: &str) -> Result<Grid<image::Rgb>, ParseError> {
+    Ok(S::from_input(input)?.render())
+}
+
+fn explain_parsed<S: Explain>(input: &str, part: u32) -> Result<String, ParseError> {
+    Ok(S::from_input(input)?.explain(part))
+}
+
+pub(crate) fn parse_boxed<S: Solution + 'static>(
+    input: &str,
+) -> Result<Box<dyn Solver>, ParseError> {
+    Ok(Box::new(S::from_input(input)?))
+}
+
+/// Entry point of a day binary: solve the input file given as first argument, `-` for the standard input
+pub fn main<S: Solution>() {
+    let input = std::env::args().nth(1).expect("No input file");
+    let solved = S::from_file(&input).and_then(|puzzle| {
+        let name = input::name(&input);
+        for answer in [Solver::part1(&puzzle), Solver::part2(&puzzle)] {
+            println!("{}", answer.map_err(|e| e.with_file(name))?);
+        }
+        Ok(())
+    });
+    if let Err(e) = solved {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
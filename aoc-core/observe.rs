@@ -0,0 +1,290 @@
+//!
+//! Observing simulations step by step, to see what they actually did
+//!
+//! A simulation tells its [`Observer`] about each of its steps and draws a text frame of its state
+//! when the observer wants one. It can also trace events, JSON objects like
+//! `{"event":"Move","count":1,"from":2,"to":1}` telling what a step did. Solving passes `&mut ()`,
+//! which wants neither, so that nothing is drawn or traced.
+//!
+
+use crate::{image, json, Point};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Receiver of the steps of a simulation
+pub trait Observer {
+    /// A step of the simulation was made, tells whether a frame of it is wanted
+    fn step(&mut self) -> bool;
+
+    /// Frame of the state after the last step, drawn as text with a line per row
+    fn frame(&mut self, frame: &str);
+
+    /// Whether events are wanted, they are not built otherwise
+    fn tracing(&self) -> bool {
+        false
+    }
+
+    /// Event of the simulation, see [`event`]
+    fn event(&mut self, _event: json::Object) {}
+}
+
+impl dyn Observer + '_ {
+    /// Send an event, only built when tracing
+    pub fn trace(&mut self, event: impl FnOnce() -> json::Object) {
+        if self.tracing() {
+            self.event(event());
+        }
+    }
+}
+
+/// Event named by its `event` field, the fields describing it follow
+pub fn event(name: &str) -> json::Object {
+    json::Object::new().string("event", name)
+}
+
+/// JSON array of the coordinates of a point, `[x,y]`
+pub fn point(point: Point) -> String {
+    format!("[{},{}]", point.x, point.y)
+}
+
+/// Observer ignoring every step
+impl Observer for () {
+    fn step(&mut self) -> bool {
+        false
+    }
+
+    fn frame(&mut self, _: &str) {}
+}
+
+/// Observer keeping every frame and event, to test what simulations show
+#[derive(Default)]
+pub struct Recorder {
+    pub frames: Vec<String>,
+    pub events: Vec<String>,
+}
+
+impl Observer for Recorder {
+    fn step(&mut self) -> bool {
+        true
+    }
+
+    fn frame(&mut self, frame: &str) {
+        self.frames.push(frame.to_string());
+    }
+
+    fn tracing(&self) -> bool {
+        true
+    }
+
+    fn event(&mut self, event: json::Object) {
+        self.events.push(event.finish());
+    }
+}
+
+/// Observer writing frames to numbered files, `frame-000001.txt` and so on, or to PPM pictures
+/// with a pixel square per character
+pub struct Exporter {
+    dir: PathBuf,
+    /// Side of the pixel squares, pictures are only written with a scale
+    scale: Option<usize>,
+    every: u64,
+    limit: u64,
+    steps: u64,
+    written: u64,
+    error: Option<String>,
+}
+
+impl Exporter {
+    /// Exporter of text frames to a directory, created if needed. Frames of an earlier export
+    /// are removed so that they are not mixed up with new ones
+    pub fn create(dir: &Path) -> Result<Self, String> {
+        let error = |e: std::io::Error| format!("cannot write {}: {e}", dir.display());
+        std::fs::create_dir_all(dir).map_err(error)?;
+        for entry in std::fs::read_dir(dir).map_err(error)? {
+            let path = entry.map_err(error)?.path();
+            let name = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or("");
+            if name.starts_with("frame-") && (name.ends_with(".txt") || name.ends_with(".ppm")) {
+                std::fs::remove_file(&path).map_err(error)?;
+            }
+        }
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            scale: None,
+            every: 1,
+            limit: u64::MAX,
+            steps: 0,
+            written: 0,
+            error: None,
+        })
+    }
+
+    /// Write pictures instead of text, with characters drawn as squares of `scale` pixels
+    pub fn pictures(self, scale: usize) -> Self {
+        Self {
+            scale: Some(scale.max(1)),
+            ..self
+        }
+    }
+
+    /// Only write a frame every `steps` steps, starting with the first one
+    pub fn every(self, steps: u64) -> Self {
+        Self {
+            every: steps.max(1),
+            ..self
+        }
+    }
+
+    /// Stop writing after `frames` frames
+    pub fn limit(self, frames: u64) -> Self {
+        Self {
+            limit: frames,
+            ..self
+        }
+    }
+
+    /// Count of frames written, or the first error writing them
+    pub fn finish(self) -> Result<u64, String> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.written),
+        }
+    }
+}
+
+impl Observer for Exporter {
+    fn step(&mut self) -> bool {
+        self.steps += 1;
+        self.error.is_none()
+            && self.written < self.limit
+            && (self.steps - 1).is_multiple_of(self.every)
+    }
+
+    fn frame(&mut self, frame: &str) {
+        self.written += 1;
+        let (extension, data) = match self.scale {
+            Some(scale) => (
+                "ppm",
+                image::ppm(&image::scaled(&image::text(frame), scale)),
+            ),
+            None => ("txt", frame.as_bytes().to_vec()),
+        };
+        let path = self
+            .dir
+            .join(format!("frame-{:06}.{extension}", self.written));
+        if let Err(e) = std::fs::write(&path, data) {
+            self.error = Some(format!("cannot write {}: {e}", path.display()));
+        }
+    }
+}
+
+/// Observer writing events to a JSON lines file, an object per line
+pub struct Tracer {
+    path: PathBuf,
+    writer: BufWriter<std::fs::File>,
+    written: u64,
+    error: Option<String>,
+}
+
+impl Tracer {
+    /// Tracer to a new file, its directory is created if needed
+    pub fn create(path: &Path) -> Result<Self, String> {
+        let error = |e: std::io::Error| format!("cannot write {}: {e}", path.display());
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(error)?;
+        }
+        let file = std::fs::File::create(path).map_err(error)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            writer: BufWriter::new(file),
+            written: 0,
+            error: None,
+        })
+    }
+
+    /// Count of events written, or the first error writing them
+    pub fn finish(mut self) -> Result<u64, String> {
+        if self.error.is_none() {
+            if let Err(e) = self.writer.flush() {
+                self.error = Some(format!("cannot write {}: {e}", self.path.display()));
+            }
+        }
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.written),
+        }
+    }
+}
+
+impl Observer for Tracer {
+    fn step(&mut self) -> bool {
+        false
+    }
+
+    fn frame(&mut self, _: &str) {}
+
+    fn tracing(&self) -> bool {
+        self.error.is_none()
+    }
+
+    fn event(&mut self, event: json::Object) {
+        self.written += 1;
+        if let Err(e) = writeln!(self.writer, "{}", event.finish()) {
+            self.error = Some(format!("cannot write {}: {e}", self.path.display()));
+        }
+    }
+}
+
+/// Frames are numbered from 1, skipped frames are not drawn
+#[test]
+fn exported() {
+    let dir = std::env::temp_dir().join(format!("aoc-frames-{}", std::process::id()));
+    let mut exporter = Exporter::create(&dir).unwrap().every(2).limit(3);
+    let mut drawn = 0;
+    for step in 0..10 {
+        if exporter.step() {
+            drawn += 1;
+            exporter.frame(&format!("{step}\n"));
+        }
+    }
+    assert_eq!(exporter.finish(), Ok(3));
+    assert_eq!(drawn, 3);
+    assert_eq!(
+        std::fs::read_to_string(dir.join("frame-000003.txt")).unwrap(),
+        "4\n"
+    );
+
+    // A new export replaces the frames of the previous one
+    let mut exporter = Exporter::create(&dir).unwrap().pictures(2);
+    assert!(exporter.step());
+    exporter.frame("#.\n");
+    assert_eq!(exporter.finish(), Ok(1));
+    let files = std::fs::read_dir(&dir).unwrap().count();
+    let picture = std::fs::read(dir.join("frame-000001.ppm")).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(files, 1);
+    assert!(picture.starts_with(b"P6\n4 2\n255\n"));
+}
+
+/// Events are only built when tracing and written one per line
+#[test]
+fn traced() {
+    let observer: &mut dyn Observer = &mut ();
+    observer.trace(|| unreachable!("event built without tracing"));
+
+    let path = std::env::temp_dir().join(format!("aoc-trace-{}.jsonl", std::process::id()));
+    let mut tracer = Tracer::create(&path).unwrap();
+    let observer: &mut dyn Observer = &mut tracer;
+    observer.trace(|| event("Move").value("count", 1).value("from", 2));
+    observer.trace(|| event("Rest").raw("at", &point(Point::new(-1, 2))));
+    assert_eq!(tracer.finish(), Ok(2));
+    let trace = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        trace,
+        "{\"event\":\"Move\",\"count\":1,\"from\":2}\n{\"event\":\"Rest\",\"at\":[-1,2]}\n"
+    );
+}
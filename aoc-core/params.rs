@@ -0,0 +1,189 @@
+//!
+//! Constants of the puzzles that can be overridden by name, like `rounds=50`, to solve variants
+//! of a puzzle without changing its solution
+//!
+
+use crate::Point;
+use std::str::FromStr;
+
+/// Value of a parameter, read from and written to text
+pub trait Value {
+    /// Replace the value with the one written in `text`
+    fn set(&mut self, text: &str) -> Result<(), String>;
+
+    /// Text of the value, as read by [`Value::set`]
+    fn text(&self) -> String;
+}
+
+/// Number written in `text`
+fn number<T: FromStr>(text: &str) -> Result<T, String> {
+    text.parse()
+        .map_err(|_| format!("expected a number, found `{text}`"))
+}
+
+impl Value for u32 {
+    fn set(&mut self, text: &str) -> Result<(), String> {
+        *self = number(text)?;
+        Ok(())
+    }
+
+    fn text(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Value for u64 {
+    fn set(&mut self, text: &str) -> Result<(), String> {
+        *self = number(text)?;
+        Ok(())
+    }
+
+    fn text(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl Value for usize {
+    fn set(&mut self, text: &str) -> Result<(), String> {
+        *self = number(text)?;
+        Ok(())
+    }
+
+    fn text(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Points are written like in the inputs, `x,y`
+impl Value for Point {
+    fn set(&mut self, text: &str) -> Result<(), String> {
+        let (x, y) = text
+            .split_once(',')
+            .ok_or_else(|| format!("expected a point `x,y`, found `{text}`"))?;
+        *self = Point::new(number(x)?, number(y)?);
+        Ok(())
+    }
+
+    fn text(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Lists are written with their numbers separated by commas, like `20,60,100`
+impl Value for Vec<u32> {
+    fn set(&mut self, text: &str) -> Result<(), String> {
+        *self = if text.is_empty() {
+            Vec::new()
+        } else {
+            text.split(',').map(number).collect::<Result<_, _>>()?
+        };
+        Ok(())
+    }
+
+    fn text(&self) -> String {
+        let items = self.iter().map(u32::to_string).collect::<Vec<_>>();
+        items.join(",")
+    }
+}
+
+/// Named constants of a puzzle, their default values being the ones of the puzzle
+pub trait Params {
+    /// Every parameter along with its name, in the order they are listed
+    fn fields(&mut self) -> Vec<(&'static str, &mut dyn Value)>;
+
+    /// Check the values once set, as the solution relies on them
+    fn check(&self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Override parameters with settings like `rounds=50`, a parameter set twice keeps its last value
+pub fn apply(params: &mut dyn Params, settings: &[String]) -> Result<(), String> {
+    for setting in settings {
+        let (name, text) = setting
+            .split_once('=')
+            .ok_or_else(|| format!("expected a parameter `name=value`, found `{setting}`"))?;
+        let mut fields = params.fields();
+        let names = fields.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        let (_, value) = fields
+            .iter_mut()
+            .find(|(field, _)| *field == name)
+            .ok_or_else(|| {
+                format!(
+                    "unknown parameter `{name}`, expected one of `{}`",
+                    names.join("`, `")
+                )
+            })?;
+        value
+            .set(text)
+            .map_err(|e| format!("invalid value for parameter `{name}`: {e}"))?;
+    }
+    params.check()
+}
+
+/// Name and value of every parameter
+pub fn values(params: &mut dyn Params) -> Vec<(&'static str, String)> {
+    params
+        .fields()
+        .into_iter()
+        .map(|(name, value)| (name, value.text()))
+        .collect()
+}
+
+#[cfg(test)]
+#[derive(Default)]
+struct Sample {
+    rounds: u32,
+    source: Point,
+    cycles: Vec<u32>,
+}
+
+#[cfg(test)]
+impl Params for Sample {
+    fn fields(&mut self) -> Vec<(&'static str, &mut dyn Value)> {
+        vec![
+            ("rounds", &mut self.rounds),
+            ("source", &mut self.source),
+            ("cycles", &mut self.cycles),
+        ]
+    }
+
+    fn check(&self) -> Result<(), String> {
+        match self.rounds {
+            0 => Err(String::from("at least one round is needed")),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Settings override the parameters by name and are checked once all applied
+#[test]
+fn applied() {
+    let settings = |settings: &[&str]| settings.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+    let mut params = Sample::default();
+    apply(
+        &mut params,
+        &settings(&["rounds=5", "source=500,0", "cycles=20,60", "rounds=7"]),
+    )
+    .unwrap();
+    assert_eq!(
+        values(&mut params),
+        [("rounds", "7"), ("source", "500,0"), ("cycles", "20,60")]
+            .map(|(name, value)| (name, value.to_string()))
+    );
+
+    let error = |setting: &str| apply(&mut Sample::default(), &settings(&[setting])).unwrap_err();
+    assert_eq!(
+        error("depth=3"),
+        "unknown parameter `depth`, expected one of `rounds`, `source`, `cycles`"
+    );
+    assert_eq!(
+        error("rounds"),
+        "expected a parameter `name=value`, found `rounds`"
+    );
+    assert_eq!(
+        error("source=1"),
+        "invalid value for parameter `source`: expected a point `x,y`, found `1`"
+    );
+    assert_eq!(error("rounds=0"), "at least one round is needed");
+}
@@ -0,0 +1,345 @@
+//!
+//! Helpers to parse puzzle inputs while keeping track of locations for error reporting
+//!
+
+use crate::ParseError;
+use std::str::FromStr;
+
+/// A line of the input along with its number, starting at 1
+#[derive(Clone, Copy, Debug)]
+pub struct Line<'a> {
+    pub number: usize,
+    pub text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    pub fn scan(&self) -> Scanner<'a> {
+        Scanner {
+            line: *self,
+            position: 0,
+        }
+    }
+
+    /// Error pointing at a column of the line, starting at 1
+    pub fn error(&self, column: usize, expected: impl Into<String>) -> ParseError {
+        let mut scanner = self.scan();
+        while scanner.column() < column && scanner.next_char().is_some() {}
+        scanner.error(expected)
+    }
+
+    /// Blank line following this one, to report what is missing after it
+    pub fn following(&self) -> Line<'static> {
+        Line {
+            number: self.number + 1,
+            text: "",
+        }
+    }
+
+    /// Error pointing right after the end of the line
+    pub fn error_at_end(&self, expected: impl Into<String>) -> ParseError {
+        self.error(self.text.chars().count() + 1, expected)
+    }
+}
+
+/// Split the input in lines, the final newline does not start an extra line
+pub fn lines(input: &str) -> Vec<Line<'_>> {
+    let input = input.strip_suffix('\n').unwrap_or(input);
+    if input.is_empty() {
+        return Vec::new();
+    }
+    input
+        .split('\n')
+        .enumerate()
+        .map(|(index, text)| Line {
+            number: index + 1,
+            text,
+        })
+        .collect()
+}
+
+/// Split the input in blocks of lines separated by a blank line, blocks are never empty
+pub fn blocks(input: &str) -> Result<Vec<Vec<Line<'_>>>, ParseError> {
+    let mut blocks = vec![Vec::new()];
+    for line in lines(input) {
+        if !line.text.is_empty() {
+            blocks.last_mut().unwrap().push(line);
+        } else if blocks.last().unwrap().is_empty() {
+            return Err(line.error(1, "a line that is not blank"));
+        } else {
+            blocks.push(Vec::new());
+        }
+    }
+    if blocks.last().unwrap().is_empty() {
+        blocks.pop();
+    }
+    Ok(blocks)
+}
+
+/// Error pointing right after the last line of the input
+pub fn end_of_input(input: &str, expected: impl Into<String>) -> ParseError {
+    ParseError {
+        file: None,
+        line: lines(input).len() + 1,
+        column: 1,
+        expected: expected.into(),
+        found: String::from("end of input"),
+        text: String::new(),
+    }
+}
+
+/// Errors of an input gathered by a parser going on after the ones it can recover from, so that
+/// they can all be reported at once, see [`crate::Lint`]
+#[derive(Debug, Default)]
+pub struct Issues {
+    errors: Vec<ParseError>,
+}
+
+impl Issues {
+    pub fn push(&mut self, error: ParseError) {
+        self.errors.push(error);
+    }
+
+    /// Value of a result, keeping its error if any
+    pub fn check<T>(&mut self, result: Result<T, ParseError>) -> Option<T> {
+        result.map_err(|error| self.push(error)).ok()
+    }
+
+    /// Value built by a parser, or the first error it found. A parser gives no value only after
+    /// an error
+    pub fn first<T>(self, value: Option<T>) -> Result<T, ParseError> {
+        match self.errors.into_iter().next() {
+            Some(error) => Err(error),
+            None => Ok(value.expect("a parser stopping without error")),
+        }
+    }
+
+    /// Every error found, in the order they were found
+    pub fn errors(self) -> Vec<ParseError> {
+        self.errors
+    }
+}
+
+/// Cursor over a line, consuming expected tokens from left to right
+#[derive(Clone, Debug)]
+pub struct Scanner<'a> {
+    line: Line<'a>,
+    /// Byte offset of the next character in the line
+    position: usize,
+}
+
+impl<'a> Scanner<'a> {
+    /// Column of the next character, starting at 1
+    pub fn column(&self) -> usize {
+        self.line.text[..self.position].chars().count() + 1
+    }
+
+    pub fn rest(&self) -> &'a str {
+        &self.line.text[self.position..]
+    }
+
+    pub fn is_end(&self) -> bool {
+        self.position == self.line.text.len()
+    }
+
+    pub fn peek(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+
+    pub fn next_char(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += c.len_utf8();
+        Some(c)
+    }
+
+    /// Consume `tag` if the line continues with it
+    pub fn eat(&mut self, tag: &str) -> bool {
+        if self.rest().starts_with(tag) {
+            self.position += tag.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn tag(&mut self, tag: &str) -> Result<(), ParseError> {
+        if self.eat(tag) {
+            Ok(())
+        } else {
+            Err(self.error(format!("`{tag}`")))
+        }
+    }
+
+    /// Consume a character among the expected ones
+    pub fn one_of(&mut self, expected: &[char]) -> Result<char, ParseError> {
+        match self.peek() {
+            Some(c) if expected.contains(&c) => {
+                self.next_char();
+                Ok(c)
+            }
+            _ => Err(self.error(one_of(expected))),
+        }
+    }
+
+    /// Consume a decimal number, with an optional minus sign
+    pub fn number<T: FromStr>(&mut self) -> Result<T, ParseError> {
+        let rest = self.rest();
+        let sign = usize::from(rest.starts_with('-'));
+        let digits = rest[sign..].bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return Err(self.error("a number"));
+        }
+        match rest[..sign + digits].parse::<T>() {
+            Ok(number) => {
+                self.position += sign + digits;
+                Ok(number)
+            }
+            Err(_) => Err(self.error(format!(
+                "a number fitting in `{}`",
+                std::any::type_name::<T>()
+            ))),
+        }
+    }
+
+    /// Consume characters until a whitespace or the end of the line
+    pub fn word(&mut self) -> &'a str {
+        let rest = self.rest();
+        let word = rest.split(char::is_whitespace).next().unwrap_or(rest);
+        self.position += word.len();
+        word
+    }
+
+    /// Consume the rest of the line
+    pub fn take_rest(&mut self) -> &'a str {
+        let rest = self.rest();
+        self.position = self.line.text.len();
+        rest
+    }
+
+    pub fn end(&self) -> Result<(), ParseError> {
+        if self.is_end() {
+            Ok(())
+        } else {
+            Err(self.error("end of line"))
+        }
+    }
+
+    /// Error pointing at the next character
+    pub fn error(&self, expected: impl Into<String>) -> ParseError {
+        ParseError {
+            file: None,
+            line: self.line.number,
+            column: self.column(),
+            expected: expected.into(),
+            found: self.found(),
+            text: self.line.text.to_string(),
+        }
+    }
+
+    /// Description of the token starting at the next character
+    fn found(&self) -> String {
+        let rest = self.rest();
+        let token = rest
+            .split(|c: char| !c.is_alphanumeric())
+            .next()
+            .unwrap_or_default();
+        match rest.chars().next() {
+            None => String::from("end of line"),
+            Some(c) if c.is_whitespace() => String::from("whitespace"),
+            Some(c) if token.is_empty() => format!("`{c}`"),
+            Some(_) => format!("`{token}`"),
+        }
+    }
+}
+
+/// Human readable enumeration of expected characters, like "`A`, `B` or `C`"
+pub fn one_of(expected: &[char]) -> String {
+    let mut result = String::new();
+    for (index, c) in expected.iter().enumerate() {
+        if index > 0 {
+            result.push_str(if index + 1 == expected.len() {
+                " or "
+            } else {
+                ", "
+            });
+        }
+        result.push('`');
+        result.push(*c);
+        result.push('`');
+    }
+    result
+}
+
+/// Final newline is optional and blank lines separate blocks
+#[test]
+fn split() {
+    let numbers = |lines: Vec<Line>| lines.iter().map(|l| l.number).collect::<Vec<_>>();
+    assert_eq!(numbers(lines("a\nb\n")), [1, 2]);
+    assert_eq!(numbers(lines("a\nb")), [1, 2]);
+    assert_eq!(numbers(lines("")), []);
+
+    let split = blocks("a\nb\n\nc\n").unwrap();
+    assert_eq!(split.len(), 2);
+    assert_eq!(numbers(split[1].clone()), [4]);
+    assert_eq!(blocks("a\n\n\nc\n").unwrap_err().line, 3);
+    assert_eq!(blocks("\na\n").unwrap_err().line, 1);
+}
+
+/// Tokens are consumed from left to right and errors point at the first unexpected one
+#[test]
+fn scanner() {
+    let line = Line {
+        number: 3,
+        text: "move 12 frm 3",
+    };
+    let mut scanner = line.scan();
+    assert_eq!(scanner.tag("move "), Ok(()));
+    assert_eq!(scanner.number::<u32>(), Ok(12));
+    let error = scanner.tag(" from ").unwrap_err();
+    assert_eq!((error.line, error.column), (3, 8));
+    assert_eq!(error.expected, "` from `");
+    assert_eq!(error.found, "whitespace");
+
+    let mut scanner = line.scan();
+    assert_eq!(scanner.word(), "move");
+    assert_eq!(
+        scanner.one_of(&['A', 'B']).unwrap_err().expected,
+        "`A` or `B`"
+    );
+    assert_eq!(scanner.number::<u8>().unwrap_err().found, "whitespace");
+    scanner.next_char();
+    assert!(scanner.number::<i8>().is_ok());
+    assert_eq!(scanner.end().unwrap_err().column, 8);
+
+    let line = Line {
+        number: 1,
+        text: "-300",
+    };
+    assert_eq!(line.scan().number::<i32>(), Ok(-300));
+    assert_eq!(line.scan().number::<u32>().unwrap_err().found, "`-`");
+    assert_eq!(
+        line.scan().number::<i8>().unwrap_err().expected,
+        "a number fitting in `i8`"
+    );
+}
+
+/// The first error is kept as the error of the parser, and every one as its issues
+#[test]
+fn issues() {
+    let line = Line {
+        number: 1,
+        text: "a b",
+    };
+    let mut issues = Issues::default();
+    assert_eq!(issues.check(line.scan().number::<u32>()), None);
+    assert_eq!(issues.check(Ok(2)), Some(2));
+    issues.push(line.error(3, "a number"));
+    assert_eq!(
+        issues.errors().iter().map(|e| e.column).collect::<Vec<_>>(),
+        [1, 3]
+    );
+
+    let mut issues = Issues::default();
+    issues.push(line.error(3, "a number"));
+    assert_eq!(issues.first(None::<u32>).unwrap_err().column, 3);
+    assert_eq!(Issues::default().first(Some(4)), Ok(4));
+}
@@ -0,0 +1,91 @@
+//!
+//! Small seeded pseudo-random generator, the same seed always gives the same sequence
+//!
+
+use std::ops::RangeInclusive;
+
+/// SplitMix64 generator, fast and good enough to build puzzle inputs
+#[derive(Clone, Debug)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Number in `0..bound`, `bound` cannot be zero
+    pub fn below(&mut self, bound: usize) -> usize {
+        assert!(bound != 0, "empty range");
+        ((u128::from(self.next_u64()) * bound as u128) >> 64) as usize
+    }
+
+    /// Number in an inclusive range
+    pub fn range(&mut self, range: RangeInclusive<usize>) -> usize {
+        let (start, end) = range.into_inner();
+        assert!(start <= end, "empty range");
+        start + self.below(end - start + 1)
+    }
+
+    /// True with the given probability, between 0 and 1
+    pub fn chance(&mut self, probability: f64) -> bool {
+        let unit = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        unit < probability
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len())]
+    }
+
+    /// Lowercase ASCII letter among the first `count` ones of the alphabet
+    pub fn letter(&mut self, count: usize) -> char {
+        char::from(b'a' + self.below(count.min(26)) as u8)
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Sequences only depend on the seed
+#[test]
+fn deterministic() {
+    let mut first = Rng::new(42);
+    let mut second = Rng::new(42);
+    for _ in 0..100 {
+        assert_eq!(first.next_u64(), second.next_u64());
+    }
+    assert_ne!(Rng::new(1).next_u64(), Rng::new(2).next_u64());
+}
+
+/// Numbers stay in their range and cover it
+#[test]
+fn bounds() {
+    let mut rng = Rng::new(7);
+    let mut seen = [false; 6];
+    for _ in 0..1000 {
+        let value = rng.range(3..=8);
+        assert!((3..=8).contains(&value));
+        seen[value - 3] = true;
+    }
+    assert!(seen.iter().all(|&seen| seen));
+    assert_eq!(rng.range(5..=5), 5);
+    assert!(!rng.chance(0.0));
+    assert!(rng.chance(1.0));
+
+    let mut items = [1, 2, 3, 4, 5];
+    rng.shuffle(&mut items);
+    items.sort_unstable();
+    assert_eq!(items, [1, 2, 3, 4, 5]);
+}
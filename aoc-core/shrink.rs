@@ -0,0 +1,115 @@
+//!
+//! Shrinking of failing inputs: parts of the input are removed and numbers lowered as long as it
+//! keeps failing, to report a counterexample small enough to be understood
+//!
+
+/// Smallest input found that still fails: blocks, lines and characters are removed by chunks of
+/// decreasing size, then numbers are lowered, until none of these changes keeps the input failing
+pub fn shrink(input: &str, mut fails: impl FnMut(&str) -> bool) -> String {
+    let mut lines = input.lines().map(String::from).collect::<Vec<_>>();
+    loop {
+        let before = lines.clone();
+
+        let mut blocks = lines
+            .split(String::is_empty)
+            .map(<[String]>::to_vec)
+            .collect::<Vec<_>>();
+        remove_chunks(&mut blocks, |blocks| {
+            fails(&join(&blocks.join(&String::new())))
+        });
+        lines = blocks.join(&String::new());
+
+        remove_chunks(&mut lines, |lines| fails(&join(lines)));
+
+        for index in 0..lines.len() {
+            let mut chars = lines[index].chars().collect::<Vec<_>>();
+            remove_chunks(&mut chars, |chars| {
+                let mut candidate = lines.clone();
+                candidate[index] = chars.iter().collect();
+                fails(&join(&candidate))
+            });
+            lines[index] = chars.into_iter().collect();
+
+            lower_numbers(&mut lines, index, &mut fails);
+        }
+
+        if lines == before {
+            return join(&lines);
+        }
+    }
+}
+
+fn join(lines: &[String]) -> String {
+    lines.iter().map(|line| format!("{line}\n")).collect()
+}
+
+/// Remove chunks of items while the rest still fails, halving the size of the chunks down to
+/// single items
+fn remove_chunks<T: Clone>(items: &mut Vec<T>, mut fails: impl FnMut(&[T]) -> bool) {
+    let mut size = items.len() / 2;
+    while size > 0 {
+        let mut start = 0;
+        while start < items.len() {
+            let mut candidate = items.clone();
+            candidate.drain(start..(start + size).min(items.len()));
+            if fails(&candidate) {
+                *items = candidate;
+            } else {
+                start += size;
+            }
+        }
+        size /= 2;
+    }
+}
+
+/// Lower the numbers of a line while the input still fails, trying the smallest values first
+fn lower_numbers(lines: &mut [String], index: usize, fails: &mut impl FnMut(&str) -> bool) {
+    let mut start = 0;
+    while let Some(offset) = lines[index][start..].find(|c: char| c.is_ascii_digit()) {
+        let begin = start + offset;
+        let line = &lines[index];
+        let end = line[begin..]
+            .find(|c: char| !c.is_ascii_digit())
+            .map_or(line.len(), |length| begin + length);
+        let Ok(mut value) = line[begin..end].parse::<u64>() else {
+            start = end;
+            continue;
+        };
+
+        let mut end = end;
+        'lower: while value > 0 {
+            for smaller in [0, 1, value / 2, value - 1] {
+                if smaller >= value {
+                    continue;
+                }
+                let mut candidate = lines.to_vec();
+                let line = &lines[index];
+                candidate[index] = format!("{}{smaller}{}", &line[..begin], &line[end..]);
+                if fails(&join(&candidate)) {
+                    lines[index] = candidate.swap_remove(index);
+                    end = begin + smaller.to_string().len();
+                    value = smaller;
+                    continue 'lower;
+                }
+            }
+            break;
+        }
+        start = end;
+    }
+}
+
+/// Lines, characters and numbers not needed for the failure are removed
+#[test]
+fn minimal() {
+    let large = |text: &str| {
+        text.lines()
+            .any(|line| line.parse::<u32>().is_ok_and(|number| number >= 10))
+    };
+    assert_eq!(shrink("1\n2\n35\n4\n", large), "10\n");
+
+    let pair = |text: &str| text.contains("ab") && text.lines().count() == 2;
+    assert_eq!(shrink("xyz\nzabz\n\nw\n", pair), "\nab\n");
+
+    // An input that does not fail is kept as is
+    assert_eq!(shrink("1\n2\n", |_| false), "1\n2\n");
+}
@@ -0,0 +1,310 @@
+//!
+//! Test harnesses checking the example inputs of a day against their answer manifest, the inputs
+//! of its generator against its reference solution and properties, and mutated inputs against
+//! panics
+//!
+//! Each `testNN.txt` input comes with a `testNN.expected` manifest listing its answers:
+//!
+//! ```text
+//! # Test from puzzle input
+//! part1: 13140
+//! part2:
+//!     ##..##..##..##..##..##..##..##..##..##..
+//!     ###...###...###...###...###...###...###.
+//! ```
+//!
+//! Lines starting with `#` are comments, multi-line answers are indented by four spaces and a part
+//! without answer is not checked.
+//!
+
+use crate::{fuzz, shrink, Answer, Generate, Lint, ParseError, Reference, Rng, Solution, Solver};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+
+const INDENT: &str = "    ";
+
+/// Count of generated inputs checked, their size grows with the seed
+const GENERATED_INPUTS: u64 = 20;
+
+/// Count of generated inputs a property is checked on, their size grows with the seed
+const PROPERTY_INPUTS: u64 = 50;
+
+/// Count of mutated inputs linted
+const LINT_INPUTS: u64 = 500;
+
+/// Count of mutated inputs checked, longer runs are left to `aor fuzz`
+const FUZZ_ITERATIONS: u64 = 500;
+
+/// Answers expected for an input
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Expected {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+impl Expected {
+    pub fn parse(manifest: &str) -> Result<Self, String> {
+        let mut expected = Self::default();
+
+        // Part being filled by the indented lines of a multi-line answer
+        let mut multi_line = None;
+
+        for (index, line) in manifest.lines().enumerate() {
+            if let Some(text) = line.strip_prefix(INDENT) {
+                let part = multi_line
+                    .ok_or_else(|| format!("line {}: indented line without part", index + 1))?;
+                let answer = expected.answer(part).get_or_insert_with(String::new);
+                answer.push_str(text);
+                answer.push('\n');
+                continue;
+            }
+
+            multi_line = None;
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (part, value) = line
+                .split_once(':')
+                .ok_or_else(|| format!("line {}: expected `part1:` or `part2:`", index + 1))?;
+            let part = match part {
+                "part1" => 1,
+                "part2" => 2,
+                _ => return Err(format!("line {}: unknown part `{part}`", index + 1)),
+            };
+            let value = value.trim();
+            if value.is_empty() {
+                multi_line = Some(part);
+            } else {
+                *expected.answer(part) = Some(value.to_string());
+            }
+        }
+
+        Ok(expected)
+    }
+
+    fn answer(&mut self, part: u32) -> &mut Option<String> {
+        if part == 1 {
+            &mut self.part1
+        } else {
+            &mut self.part2
+        }
+    }
+}
+
+/// Example inputs `testNN.txt` of a day directory along with their manifest, sorted by name
+pub fn examples(dir: &str) -> Vec<(PathBuf, PathBuf)> {
+    let mut examples = std::fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            name.strip_prefix("test")
+                .and_then(|name| name.strip_suffix(".txt"))
+                .is_some_and(|number| {
+                    !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit())
+                })
+        })
+        .map(|input| {
+            let manifest = input.with_extension("expected");
+            (input, manifest)
+        })
+        .collect::<Vec<_>>();
+    examples.sort();
+    examples
+}
+
+/// Solve every example input of a day directory and compare answers with their manifest
+///
+/// All the examples are checked before panicking with the list of failures
+pub fn check_examples<S: Solution>(dir: &str) {
+    let examples = examples(dir);
+    assert!(!examples.is_empty(), "no test input in {dir}");
+
+    let mut failures = Vec::new();
+    for (input, manifest) in &examples {
+        if let Err(failure) = check_example::<S>(input, manifest) {
+            failures.push(failure);
+        }
+    }
+
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}
+
+fn check_example<S: Solution>(input: &Path, manifest: &Path) -> Result<(), String> {
+    let name = input.display();
+    let manifest = std::fs::read_to_string(manifest)
+        .map_err(|e| format!("{name}: cannot read manifest {}: {e}", manifest.display()))?;
+    let expected = Expected::parse(&manifest).map_err(|e| format!("{name}: manifest {e}"))?;
+
+    let puzzle = S::from_file(&input.to_string_lossy()).map_err(|e| format!("{name}:\n{e}"))?;
+    let mut failures = Vec::new();
+    let mut compare = |part: &str, expected: &str, actual: Result<String, ParseError>| {
+        let actual = actual.unwrap_or_else(|e| e.to_string());
+        if actual.trim_end() != expected.trim_end() {
+            failures.push(format!(
+                "{name}: {part} expected\n{expected}\nbut got\n{actual}"
+            ));
+        }
+    };
+    if let Some(expected) = &expected.part1 {
+        compare("part1", expected, Solver::part1(&puzzle));
+    }
+    if let Some(expected) = &expected.part2 {
+        compare("part2", expected, Solver::part2(&puzzle));
+    }
+
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(failures.join("\n"))
+    }
+}
+
+/// Input built by the generator of a day from a seed, along with its description and puzzle
+fn generated<S: Solution + Generate>(seed: u64) -> (String, String, S) {
+    let size = seed as usize + 1;
+    let input = S::generate(&mut Rng::new(seed), size);
+    let context = format!("generated input (seed {seed}, size {size})");
+    match S::from_input(&input) {
+        Ok(puzzle) => (context, input, puzzle),
+        Err(e) => panic!("{context} is malformed:\n{e}\n{input}"),
+    }
+}
+
+/// Solve inputs built by the generator of a day, they have to be parsed and answered without panic
+/// nor error
+pub fn check_generated<S: Solution + Generate>() {
+    for seed in 0..GENERATED_INPUTS {
+        let (context, input, puzzle) = generated::<S>(seed);
+        let solved = panic::catch_unwind(AssertUnwindSafe(|| {
+            Solver::part1(&puzzle).and(Solver::part2(&puzzle))
+        }));
+        match solved {
+            Ok(Ok(_)) => (),
+            Ok(Err(e)) => panic!("{context} cannot be answered:\n{e}\n{input}"),
+            Err(_) => panic!("{context} cannot be solved:\n{input}"),
+        }
+    }
+}
+
+/// Compare answers with the ones of the reference solution on generated inputs, reporting the first
+/// input they disagree on
+pub fn check_reference<S: Solution + Generate + Reference>() {
+    for seed in 0..GENERATED_INPUTS {
+        let (context, input, puzzle) = generated::<S>(seed);
+        let answers = [
+            (
+                "part1",
+                Solution::part1(&puzzle).text(),
+                puzzle.reference_part1().text(),
+            ),
+            (
+                "part2",
+                Solution::part2(&puzzle).text(),
+                puzzle.reference_part2().text(),
+            ),
+        ];
+        for (part, answer, expected) in answers {
+            // Both fail alike on an input they cannot answer
+            let [answer, expected] =
+                [answer, expected].map(|text| text.unwrap_or_else(|e| e.to_string()));
+            assert!(
+                answer == expected,
+                "{part} of {context} is\n{answer}\nbut the reference gives\n{expected}\n{input}"
+            );
+        }
+    }
+}
+
+/// Check a property of the puzzle on generated inputs. The first input it fails on is shrunk to a
+/// minimal one, which is saved to a file of the temporary directory
+pub fn check_property<S: Solution + Generate>(
+    name: &str,
+    property: impl Fn(&S) -> Result<(), String>,
+) {
+    let check = |puzzle: &S| {
+        panic::catch_unwind(AssertUnwindSafe(|| property(puzzle)))
+            .unwrap_or_else(|_| Err(String::from("panic")))
+    };
+    for seed in 0..PROPERTY_INPUTS {
+        let (context, input, puzzle) = generated::<S>(seed);
+        if check(&puzzle).is_ok() {
+            continue;
+        }
+
+        let minimal = shrink::shrink(&input, |input| {
+            S::from_input(input).is_ok_and(|puzzle| check(&puzzle).is_err())
+        });
+        let message = check(&S::from_input(&minimal).unwrap()).unwrap_err();
+        let day = std::any::type_name::<S>().split("::").next().unwrap();
+        let path = std::env::temp_dir().join(format!("{day}-{name}.txt"));
+        std::fs::write(&path, &minimal).unwrap();
+        panic!(
+            "property `{name}` fails on {context}, shrunk to {}: {message}\n{minimal}",
+            path.display()
+        );
+    }
+}
+
+/// Feed the corpus of a day and mutations of it to its parser and solver, none of them may panic
+pub fn check_fuzz<S: Solution + 'static>(dir: &str) {
+    let corpus = fuzz::corpus(dir);
+    let result = fuzz::run(
+        crate::parse_boxed::<S>,
+        &corpus,
+        &mut Rng::new(0),
+        FUZZ_ITERATIONS,
+    );
+    if let Err(crash) = result {
+        panic!(
+            "input {:?} panics: {}",
+            String::from_utf8_lossy(&crash.input),
+            crash.message
+        );
+    }
+}
+
+/// Lint the corpus of a day and mutations of it: an input has errors if and only if it does not
+/// parse, the error of the parser being one of them
+pub fn check_lint<S: Lint>(dir: &str) {
+    let corpus = fuzz::corpus(dir);
+    let mut rng = Rng::new(0);
+    let mut inputs = corpus.clone();
+    for _ in 0..LINT_INPUTS {
+        let mut input = rng.pick(&corpus).clone();
+        for _ in 0..rng.range(1..=4) {
+            fuzz::mutate(&mut rng, &mut input, &corpus);
+        }
+        inputs.push(input);
+    }
+
+    for input in inputs {
+        let input = crate::input::normalize(&String::from_utf8_lossy(&input));
+        let errors = S::lint(&input);
+        match S::parse(&input) {
+            Ok(_) => assert!(errors.is_empty(), "{input:?} parses with {errors:?}"),
+            Err(error) => assert!(
+                errors.contains(&error),
+                "{input:?} fails with {error:?} missing from {errors:?}"
+            ),
+        }
+    }
+}
+
+/// Single and multi-line answers can be mixed with comments
+#[test]
+fn manifest() {
+    let manifest = "# Test\npart1: 12\npart2:\n    #..\n    .#.\n";
+    let expected = Expected::parse(manifest).unwrap();
+    assert_eq!(expected.part1.as_deref(), Some("12"));
+    assert_eq!(expected.part2.as_deref(), Some("#..\n.#.\n"));
+
+    let expected = Expected::parse("part2: CMZ\n").unwrap();
+    assert_eq!(expected.part1, None);
+    assert_eq!(expected.part2.as_deref(), Some("CMZ"));
+
+    assert!(Expected::parse("part3: 1\n").is_err());
+    assert!(Expected::parse("    #..\n").is_err());
+    assert!(Expected::parse("part1 12\n").is_err());
+}
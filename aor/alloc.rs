@@ -0,0 +1,122 @@
+//!
+//! Allocator counting the memory used by the runner, to follow the memory cost of each phase
+//! along with its duration
+//!
+//! Counters are shared by every thread, a measure is only meaningful while a single day runs.
+//!
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+static ALLOCATED: AtomicU64 = AtomicU64::new(0);
+
+/// Bytes currently in use
+static LIVE: AtomicU64 = AtomicU64::new(0);
+
+/// Highest count of bytes in use since the last measure started
+static PEAK: AtomicU64 = AtomicU64::new(0);
+
+/// System allocator keeping counts of its allocations and of the memory in use
+pub struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let pointer = System.alloc(layout);
+        if !pointer.is_null() {
+            allocated(layout.size(), 0);
+        }
+        pointer
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let pointer = System.alloc_zeroed(layout);
+        if !pointer.is_null() {
+            allocated(layout.size(), 0);
+        }
+        pointer
+    }
+
+    unsafe fn dealloc(&self, pointer: *mut u8, layout: Layout) {
+        System.dealloc(pointer, layout);
+        LIVE.fetch_sub(layout.size() as u64, Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, pointer: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let moved = System.realloc(pointer, layout, new_size);
+        if !moved.is_null() {
+            allocated(new_size, layout.size());
+        }
+        moved
+    }
+}
+
+/// Count an allocation of `size` bytes replacing `freed` bytes, which a reallocation does
+fn allocated(size: usize, freed: usize) {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED.fetch_add(size as u64, Ordering::Relaxed);
+    LIVE.fetch_sub(freed as u64, Ordering::Relaxed);
+    let live = LIVE.fetch_add(size as u64, Ordering::Relaxed) + size as u64;
+    PEAK.fetch_max(live, Ordering::Relaxed);
+}
+
+/// Memory allocated during a phase
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Usage {
+    /// Count of allocations, reallocations included
+    pub allocations: u64,
+    /// Bytes requested in total, a reallocation counting its new size
+    pub bytes: u64,
+    /// Highest count of bytes in use on top of those in use when the phase started
+    pub peak: u64,
+}
+
+/// Run `f` and count the memory it allocated
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Usage) {
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let bytes = ALLOCATED.load(Ordering::Relaxed);
+    let live = LIVE.load(Ordering::Relaxed);
+    PEAK.store(live, Ordering::Relaxed);
+
+    let result = f();
+    let usage = Usage {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+        bytes: ALLOCATED.load(Ordering::Relaxed) - bytes,
+        peak: PEAK.load(Ordering::Relaxed).saturating_sub(live),
+    };
+    (result, usage)
+}
+
+/// Count of bytes with a binary unit, like `1.5 MiB`
+pub fn size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+/// Allocations of a phase are counted, whatever the other threads do
+#[test]
+fn counted() {
+    let (data, usage) = measure(|| vec![7u8; 10_000]);
+    assert_eq!(data.len(), 10_000);
+    assert!(usage.allocations >= 1);
+    assert!(usage.bytes >= 10_000);
+}
+
+/// Sizes are written with the largest unit below them
+#[test]
+fn sizes() {
+    assert_eq!(size(0), "0 B");
+    assert_eq!(size(1023), "1023 B");
+    assert_eq!(size(1536), "1.5 KiB");
+    assert_eq!(size(3 << 30), "3.0 GiB");
+}
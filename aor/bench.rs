@@ -0,0 +1,159 @@
+//!
+//! Statistics over repeated runs of a phase, and baselines to compare them between runs
+//!
+//! A baseline is a text file with one line per day and phase, durations are in nanoseconds:
+//!
+//! ```text
+//! # day phase min median mean stddev
+//! 11 part2 10480233 10611402 10650871 120388
+//! ```
+//!
+
+use std::time::{Duration, Instant};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    pub fn new(mut samples: Vec<Duration>) -> Self {
+        assert!(!samples.is_empty());
+        samples.sort_unstable();
+
+        let count = samples.len();
+        let median = if count.is_multiple_of(2) {
+            (samples[count / 2 - 1] + samples[count / 2]) / 2
+        } else {
+            samples[count / 2]
+        };
+
+        let nanos = samples
+            .iter()
+            .map(|s| s.as_nanos() as f64)
+            .collect::<Vec<_>>();
+        let mean = nanos.iter().sum::<f64>() / count as f64;
+        let variance = nanos.iter().map(|n| (n - mean).powi(2)).sum::<f64>() / count as f64;
+
+        Self {
+            min: samples[0],
+            median,
+            mean: Duration::from_nanos(mean.round() as u64),
+            stddev: Duration::from_nanos(variance.sqrt().round() as u64),
+        }
+    }
+}
+
+/// Run `f` the given count of times and compute statistics over its durations
+pub fn measure(iterations: u32, mut f: impl FnMut()) -> Stats {
+    let samples = (0..iterations)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .collect();
+    Stats::new(samples)
+}
+
+/// Statistics of a phase of a day
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub day: u32,
+    pub phase: String,
+    pub stats: Stats,
+}
+
+pub fn write(path: &str, records: &[Record]) -> Result<(), String> {
+    let mut data = String::from("# day phase min median mean stddev\n");
+    for record in records {
+        let stats = &record.stats;
+        data.push_str(&format!(
+            "{} {} {} {} {} {}\n",
+            record.day,
+            record.phase,
+            stats.min.as_nanos(),
+            stats.median.as_nanos(),
+            stats.mean.as_nanos(),
+            stats.stddev.as_nanos()
+        ));
+    }
+    std::fs::write(path, data).map_err(|e| format!("cannot write {path}: {e}"))
+}
+
+pub fn read(path: &str) -> Result<Vec<Record>, String> {
+    let data = std::fs::read_to_string(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+
+    let mut records = Vec::new();
+    for (index, line) in data.lines().enumerate() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let malformed = || format!("{path}:{}: malformed baseline entry", index + 1);
+        let fields = line.split(' ').collect::<Vec<_>>();
+        let [day, phase, min, median, mean, stddev] = fields[..] else {
+            return Err(malformed());
+        };
+        let duration = |field: &str| {
+            field
+                .parse::<u64>()
+                .map(Duration::from_nanos)
+                .map_err(|_| malformed())
+        };
+        records.push(Record {
+            day: day.parse().map_err(|_| malformed())?,
+            phase: phase.to_string(),
+            stats: Stats {
+                min: duration(min)?,
+                median: duration(median)?,
+                mean: duration(mean)?,
+                stddev: duration(stddev)?,
+            },
+        });
+    }
+    Ok(records)
+}
+
+/// Relative change of the median compared to a baseline, in percent
+pub fn change(baseline: &Stats, stats: &Stats) -> f64 {
+    let baseline = baseline.median.as_nanos() as f64;
+    let median = stats.median.as_nanos() as f64;
+    (median - baseline) / baseline.max(1.0) * 100.0
+}
+
+/// Statistics are computed over sorted samples
+#[test]
+fn stats() {
+    let samples = [4, 1, 3, 2].map(Duration::from_nanos).to_vec();
+    let stats = Stats::new(samples);
+    assert_eq!(stats.min, Duration::from_nanos(1));
+    assert_eq!(stats.median, Duration::from_nanos(2));
+    assert_eq!(stats.mean, Duration::from_nanos(3));
+    assert_eq!(stats.stddev, Duration::from_nanos(1));
+
+    let samples = [7, 1, 4].map(Duration::from_nanos).to_vec();
+    assert_eq!(Stats::new(samples).median, Duration::from_nanos(4));
+}
+
+/// A baseline is read back as it was written
+#[test]
+fn baseline() {
+    let records = vec![Record {
+        day: 11,
+        phase: String::from("part2"),
+        stats: Stats {
+            min: Duration::from_nanos(10),
+            median: Duration::from_nanos(12),
+            mean: Duration::from_nanos(13),
+            stddev: Duration::from_nanos(2),
+        },
+    }];
+    let path = std::env::temp_dir().join(format!("aor-baseline-{}.txt", std::process::id()));
+    let path = path.to_str().unwrap();
+    write(path, &records).unwrap();
+    assert_eq!(read(path), Ok(records));
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,109 @@
+//!
+//! Minimal command line parsing: `--name value` options, `--name` switches and positional arguments
+//!
+
+pub struct Args {
+    options: Vec<(String, String)>,
+    switches: Vec<String>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    /// Parse arguments, rejecting any option that is not in `options` or `switches`
+    pub fn parse(args: &[String], options: &[&str], switches: &[&str]) -> Result<Self, String> {
+        let mut result = Self {
+            options: Vec::new(),
+            switches: Vec::new(),
+            positional: Vec::new(),
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let name = match arg.strip_prefix("--") {
+                Some(name) if !name.is_empty() => name,
+                _ => {
+                    result.positional.push(arg.clone());
+                    continue;
+                }
+            };
+
+            if switches.contains(&name) {
+                result.switches.push(name.to_string());
+            } else if options.contains(&name) {
+                let value = args
+                    .next()
+                    .ok_or_else(|| format!("missing value for `--{name}`"))?;
+                result.options.push((name.to_string(), value.clone()));
+            } else {
+                return Err(format!("unknown option `--{name}`"));
+            }
+        }
+
+        Ok(result)
+    }
+
+    /// Last value given to an option
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.options
+            .iter()
+            .rev()
+            .find(|(option, _)| option == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Every value given to an option, in order
+    pub fn values(&self, name: &str) -> Vec<String> {
+        self.options
+            .iter()
+            .filter(|(option, _)| option == name)
+            .map(|(_, value)| value.clone())
+            .collect()
+    }
+
+    /// Last value given to an option, parsed as a number
+    pub fn number<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>, String> {
+        self.value(name)
+            .map(|value| {
+                value
+                    .parse::<T>()
+                    .map_err(|_| format!("invalid value `{value}` for `--{name}`"))
+            })
+            .transpose()
+    }
+
+    pub fn switch(&self, name: &str) -> bool {
+        self.switches.iter().any(|switch| switch == name)
+    }
+
+    pub fn positional(&self) -> &[String] {
+        &self.positional
+    }
+}
+
+/// Options and positional arguments can be mixed
+#[test]
+fn parse() {
+    let args = [
+        "--day",
+        "11",
+        "input.txt",
+        "--all",
+        "--part",
+        "2",
+        "--part",
+        "1",
+    ]
+    .map(String::from);
+    let args = Args::parse(&args, &["day", "part"], &["all"]).unwrap();
+    assert_eq!(args.number::<u32>("day"), Ok(Some(11)));
+    assert_eq!(args.number::<u32>("part"), Ok(Some(1)));
+    assert_eq!(args.values("part"), ["2", "1"]);
+    assert!(args.switch("all"));
+    assert_eq!(args.positional(), ["input.txt"]);
+
+    let args = ["--day"].map(String::from);
+    assert!(Args::parse(&args, &["day"], &[]).is_err());
+
+    let args = ["--days", "1"].map(String::from);
+    assert!(Args::parse(&args, &["day"], &[]).is_err());
+}
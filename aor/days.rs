@@ -0,0 +1,34 @@
+//!
+//! Registry of every day known by the runner
+//!
+
+use aoc_core::Day;
+
+pub const DAYS: [Day; 14] = [
+    day01::DAY,
+    day02::DAY,
+    day03::DAY,
+    day04::DAY,
+    day05::DAY,
+    day06::DAY,
+    day07::DAY,
+    day08::DAY,
+    day09::DAY,
+    day10::DAY,
+    day11::DAY,
+    day12::DAY,
+    day13::DAY,
+    day14::DAY,
+];
+
+pub fn find(number: u32) -> Option<&'static Day> {
+    DAYS.iter().find(|day| day.number == number)
+}
+
+/// Days must be registered in order, without any gap
+#[test]
+fn registry() {
+    for (index, day) in DAYS.iter().enumerate() {
+        assert_eq!(day.number as usize, index + 1);
+    }
+}
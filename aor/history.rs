@@ -0,0 +1,244 @@
+//!
+//! History of the answers and durations of every recorded run, to follow them across revisions
+//!
+//! The history is an append-only text file with one line per part solved. The input is identified
+//! by its hash and answers are escaped to fit on the line:
+//!
+//! ```text
+//! # timestamp revision day part input elapsed_ns answer
+//! 1792317432 62a5e7c 10 2 9c3e1f0a5b7d2e48 18506 ##..##\n###...\n
+//! ```
+//!
+
+use std::io::Write;
+use std::time::Duration;
+
+const HEADER: &str = "# timestamp revision day part input elapsed_ns answer\n";
+
+/// Run appending records to the history: when it happened and on which revision
+#[derive(Clone, Debug, PartialEq)]
+pub struct Run {
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Git revision of the workspace, marked `-dirty` with uncommitted changes
+    pub revision: String,
+}
+
+impl Run {
+    pub fn now() -> Self {
+        Self {
+            timestamp: timestamp(),
+            revision: revision(),
+        }
+    }
+}
+
+/// Seconds since the Unix epoch, as recorded by [`Run::now`]
+pub fn timestamp() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Answer to a part of a day and the time it took
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record {
+    pub day: u32,
+    pub part: u32,
+    /// Hash of the input, see [`hash`]
+    pub input: u64,
+    pub elapsed: Duration,
+    pub answer: String,
+}
+
+/// FNV-1a hash of an input, enough to tell inputs apart
+pub fn hash(data: &str) -> u64 {
+    data.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Short hash of the current git revision, or `unknown` outside of a git repository
+fn revision() -> String {
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+    match git(&["rev-parse", "--short", "HEAD"]) {
+        Some(revision) if git(&["status", "--porcelain"]).is_some_and(|s| !s.is_empty()) => {
+            format!("{revision}-dirty")
+        }
+        Some(revision) => revision,
+        None => String::from("unknown"),
+    }
+}
+
+fn escape(answer: &str) -> String {
+    let mut escaped = String::new();
+    for c in answer.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(escaped: &str) -> Option<String> {
+    let mut answer = String::new();
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            answer.push(c);
+            continue;
+        }
+        match chars.next()? {
+            '\\' => answer.push('\\'),
+            'n' => answer.push('\n'),
+            'r' => answer.push('\r'),
+            _ => return None,
+        }
+    }
+    Some(answer)
+}
+
+/// Append the records of a run, creating the history and its directory when needed
+pub fn append(path: &str, run: &Run, records: &[Record]) -> Result<(), String> {
+    let error = |e: std::io::Error| format!("cannot write {path}: {e}");
+    if let Some(parent) = std::path::Path::new(path).parent() {
+        std::fs::create_dir_all(parent).map_err(error)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(error)?;
+
+    let mut data = String::new();
+    if file.metadata().map_err(error)?.len() == 0 {
+        data.push_str(HEADER);
+    }
+    for record in records {
+        data.push_str(&format!(
+            "{} {} {} {} {:016x} {} {}\n",
+            run.timestamp,
+            run.revision,
+            record.day,
+            record.part,
+            record.input,
+            record.elapsed.as_nanos(),
+            escape(&record.answer)
+        ));
+    }
+    file.write_all(data.as_bytes()).map_err(error)
+}
+
+/// Records of every run, in the order they were appended
+pub fn read(path: &str) -> Result<Vec<(Run, Record)>, String> {
+    let data = std::fs::read_to_string(path).map_err(|e| format!("cannot read {path}: {e}"))?;
+
+    let mut records = Vec::new();
+    for (index, line) in data.lines().enumerate() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let malformed = || format!("{path}:{}: malformed history entry", index + 1);
+        let fields = line.splitn(7, ' ').collect::<Vec<_>>();
+        let [timestamp, revision, day, part, input, elapsed, answer] = fields[..] else {
+            return Err(malformed());
+        };
+        let run = Run {
+            timestamp: timestamp.parse().map_err(|_| malformed())?,
+            revision: revision.to_string(),
+        };
+        let record = Record {
+            day: day.parse().map_err(|_| malformed())?,
+            part: part.parse().map_err(|_| malformed())?,
+            input: u64::from_str_radix(input, 16).map_err(|_| malformed())?,
+            elapsed: Duration::from_nanos(elapsed.parse().map_err(|_| malformed())?),
+            answer: unescape(answer).ok_or_else(malformed)?,
+        };
+        records.push((run, record));
+    }
+    Ok(records)
+}
+
+/// UTC date and time of a timestamp, like `2022-12-11 06:00:00`
+pub fn date(timestamp: u64) -> String {
+    let (days, seconds) = (timestamp / 86_400, timestamp % 86_400);
+
+    // Civil date from a count of days, shifting years to start in March so that leap days end them
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// A history is read back as it was appended, multi-line answers included
+#[test]
+fn history() {
+    let path = std::env::temp_dir().join(format!("aor-history-{}.txt", std::process::id()));
+    let path = path.to_str().unwrap();
+    let _ = std::fs::remove_file(path);
+
+    let run = Run {
+        timestamp: 1_670_738_400,
+        revision: String::from("62a5e7c"),
+    };
+    let records = [
+        Record {
+            day: 10,
+            part: 2,
+            input: hash("noop\n"),
+            elapsed: Duration::from_nanos(18_506),
+            answer: String::from("#.\\ #\n.#\n"),
+        },
+        Record {
+            day: 11,
+            part: 1,
+            input: 7,
+            elapsed: Duration::from_nanos(10),
+            answer: String::from("10605"),
+        },
+    ];
+    append(path, &run, &records[..1]).unwrap();
+    append(path, &run, &records[1..]).unwrap();
+
+    let read = read(path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    assert_eq!(read.len(), 2);
+    assert!(read.iter().all(|(read, _)| *read == run));
+    assert_eq!(read[0].1, records[0]);
+    assert_eq!(read[1].1, records[1]);
+}
+
+/// Dates are computed in UTC, leap years included
+#[test]
+fn dates() {
+    assert_eq!(date(0), "1970-01-01 00:00:00");
+    assert_eq!(date(1_670_738_400), "2022-12-11 06:00:00");
+    assert_eq!(date(951_782_400), "2000-02-29 00:00:00");
+}
@@ -0,0 +1,1135 @@
+//!
+//! Runner dispatching to the solution of every day
+//!
+
+mod alloc;
+mod bench;
+mod cli;
+mod days;
+mod history;
+mod summary;
+mod watch;
+
+use aoc_core::{
+    fuzz, image, input, json, observe, Configured, Day, ExplainFn, ObserveFn, RenderFn, Rng,
+};
+use cli::Args;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+#[global_allocator]
+static ALLOCATOR: alloc::Counting = alloc::Counting;
+
+const USAGE: &str = "\
+Usage:
+    aor run (--day N INPUT | --all [DIR]) [--part P] [--format FORMAT] [--record] [--history FILE]
+            [--render IMAGE] [--param NAME=VALUE]... [--explain]
+    aor bench (--day N INPUT | --all [DIR]) [--part P] [--format FORMAT] [--iterations COUNT]
+              [--save FILE] [--baseline FILE] [--threshold PERCENT] [--param NAME=VALUE]...
+    aor params --day N [--param NAME=VALUE]...
+    aor watch --day N INPUT [--part P] [--interval MILLISECONDS]
+    aor lint --day N INPUT
+    aor summary [DIR] [--days LIST] [--jobs COUNT] [--format FORMAT] [--record] [--history FILE]
+    aor history [--day N] [--part P] [--history FILE]
+    aor generate --day N [--size SIZE] [--seed SEED]
+    aor fuzz --day N [--iterations COUNT] [--seed SEED] [--corpus DIR]
+    aor animate --day N INPUT [--part P] [--dir DIR] [--every COUNT] [--limit COUNT] [--scale PIXELS]
+    aor trace --day N INPUT [--part P] [--output FILE]
+
+INPUT is a file, or `-` for the standard input. With `--all`, the input of each day is read
+from `DIR/dayNN.txt` (default DIR is `inputs`).
+
+FORMAT is `text` (default) or `json`, which prints one JSON object per line for each answer or
+phase measured.
+
+`run` and `bench` report along with the duration of each phase the memory it allocated: the count
+of allocations, the bytes allocated and the peak of memory in use above the one at its start.
+
+With `--render`, `run` also draws the state of the puzzle once solved to IMAGE, a binary PGM
+(gray) if its name ends with `.pgm` and a binary PPM (color) otherwise. Days 8, 12 and 14 can be
+rendered.
+
+With `--explain`, `run` also tells below each answer of a single day how it was found: the top
+elves of day 1, the item of each rucksack of day 3, the directory to delete of day 7, the best tree
+of day 8, the busiest monkeys of day 11, the path of day 12 and the pairs in order of day 13.
+
+With `--param`, `run` and `bench` solve a single day with one of its constants overridden, like
+`--param worry_rounds=500`, and can be repeated. `params` lists the parameters of a day along with
+their values. Days 1, 6, 7, 9, 10, 11 and 14 have parameters. Answers found with overridden
+parameters are not recorded to the history.
+
+`watch` solves INPUT each time its modification time changes, checked every MILLISECONDS (default
+500), and prints the answers next to the ones of the previous run until interrupted.
+
+`lint` parses INPUT without solving it and lists every problem found, and fails if there is any.
+Days 2, 4, 5, 7, 8, 11, 12, 13 and 14 go on after an error, the others only report the first one.
+
+`bench` runs each phase COUNT times (default 10) and reports statistics on their durations. The
+statistics can be saved to a baseline FILE, a later run compares its medians with this baseline
+and fails if any is slower by more than PERCENT (default 10).
+
+`summary` solves the days of LIST (default all of them, e.g. `1,5,10-14`) on COUNT threads
+(default one per CPU) and prints a table of their answers and durations. The input of each day is
+read from `DIR/dayNN.txt` like with `--all`, a day failing or panicking does not stop the others.
+
+With `--record`, `run` and `summary` append the answers and durations to the history FILE (default
+`target/aor-history.txt`) along with a hash of the input and the git revision. `history` lists
+them by day, part and input, and fails if an answer changed for the same input.
+
+`generate` prints a random valid input made of about SIZE items (default 100), the same SEED
+always gives the same input. Without SEED, the seed used is printed on the standard error.
+
+`fuzz` feeds COUNT inputs (default 100000) mutated from the corpus of the day to its parser and
+solver. The corpus is made of the examples `testNN.txt` and the files of `DIR/corpus` (default
+DIR is `dayNN`), an input making the day panic is saved there as `crash-SEED.txt`. Build with
+`--profile fuzz` to detect overflows while keeping optimizations.
+
+`animate` solves part P (default 1) of days 5, 9, 10, 11 and 14 step by step, writing a frame of
+each step to DIR (default `target/frames/dayNN`) as `frame-000001.txt` and so on. Only one frame
+every COUNT steps is written with `--every`, and at most COUNT frames (default 10000) with
+`--limit`. With `--scale`, frames are PPM pictures with a square of PIXELS pixels per character.
+
+`trace` solves part P (default 1) writing the events of each step to FILE (default
+`target/traces/dayNN-partP.jsonl`), one JSON object per line like
+`{\"event\":\"Move\",\"count\":1,\"from\":2,\"to\":1}`, to diff them with another implementation";
+
+const DEFAULT_INPUT_DIR: &str = "inputs";
+
+const DEFAULT_HISTORY: &str = "target/aor-history.txt";
+
+const DEFAULT_ITERATIONS: u32 = 10;
+
+const DEFAULT_SIZE: usize = 100;
+
+const DEFAULT_FUZZ_ITERATIONS: u64 = 100_000;
+
+const DEFAULT_FRAMES_DIR: &str = "target/frames";
+
+const DEFAULT_FRAME_LIMIT: u64 = 10_000;
+
+const DEFAULT_TRACES_DIR: &str = "target/traces";
+
+const DEFAULT_WATCH_INTERVAL: u64 = 500;
+
+/// Slowdown of a phase median, in percent, above which it is flagged as a regression
+const DEFAULT_THRESHOLD: f64 = 10.0;
+
+/// Output of the commands
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
+enum Error {
+    /// Bad command line, the usage is printed along with it
+    Usage(String),
+    /// The command failed, the message is a complete diagnostic
+    Failed(String),
+}
+
+impl From<String> for Error {
+    fn from(message: String) -> Self {
+        Error::Usage(message)
+    }
+}
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let result = match args.first().map(String::as_str) {
+        Some("run") => run(&args[1..]),
+        Some("bench") => bench(&args[1..]),
+        Some("summary") => summary(&args[1..]),
+        Some("history") => history(&args[1..]),
+        Some("params") => params(&args[1..]),
+        Some("watch") => watch(&args[1..]),
+        Some("lint") => lint(&args[1..]),
+        Some("generate") => generate(&args[1..]),
+        Some("fuzz") => fuzz(&args[1..]),
+        Some("animate") => animate(&args[1..]),
+        Some("trace") => trace(&args[1..]),
+        Some(command) => Err(Error::Usage(format!("unknown command `{command}`"))),
+        None => Err(Error::Usage(String::from("missing command"))),
+    };
+
+    match result {
+        Ok(()) => (),
+        Err(Error::Usage(message)) => {
+            eprintln!("error: {message}");
+            eprintln!();
+            eprintln!("{USAGE}");
+            std::process::exit(2);
+        }
+        Err(Error::Failed(message)) => {
+            eprintln!("{message}");
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(
+        args,
+        &["day", "part", "format", "history", "render", "param"],
+        &["all", "record", "explain"],
+    )?;
+    let part = part(&args)?;
+    let format = format(&args)?;
+    if args.value("render").is_some() && args.switch("all") {
+        return Err(Error::Usage(String::from(
+            "`--render` needs a single day with `--day`",
+        )));
+    }
+    if args.switch("explain") && args.switch("all") {
+        return Err(Error::Usage(String::from(
+            "`--explain` needs a single day with `--day`",
+        )));
+    }
+    let settings = settings(&args)?;
+    if !settings.is_empty() && args.switch("record") {
+        return Err(Error::Usage(String::from(
+            "answers found with `--param` cannot be recorded",
+        )));
+    }
+    if !settings.is_empty() && args.switch("explain") {
+        return Err(Error::Usage(String::from(
+            "answers found with `--param` cannot be explained",
+        )));
+    }
+    let targets = targets(&args)?;
+    let explain = if args.switch("explain") {
+        let day = targets[0].0;
+        let explain = day.explain.ok_or_else(|| {
+            Error::Failed(format!("error: day {} cannot be explained", day.number))
+        })?;
+        Some(explain)
+    } else {
+        None
+    };
+    let render = match args.value("render") {
+        Some(path) => {
+            let day = targets[0].0;
+            let render = day.render.ok_or_else(|| {
+                Error::Failed(format!("error: day {} cannot be rendered", day.number))
+            })?;
+            Some((render, path))
+        }
+        None => None,
+    };
+
+    let mut records = Vec::new();
+    let result = for_each_target(&targets, |day, input| {
+        solve(day, &settings, input, part, format, explain, &mut records)
+    });
+    if args.switch("record") {
+        append_history(&args, &records)?;
+    }
+    result?;
+
+    match render {
+        Some((render, path)) => write_picture(render, &targets[0].1, path),
+        None => Ok(()),
+    }
+}
+
+/// Draw the puzzle of an input to an image file, PGM or PPM depending on its extension
+fn write_picture(render: RenderFn, input: &str, path: &str) -> Result<(), Error> {
+    let data = read(input)?;
+    let picture =
+        render(&data).map_err(|e| Error::Failed(e.with_file(input::name(input)).to_string()))?;
+    let encoded = if path.ends_with(".pgm") {
+        image::pgm(&picture)
+    } else {
+        image::ppm(&picture)
+    };
+    std::fs::write(path, encoded).map_err(|e| Error::Failed(format!("error: {path}: {e}")))?;
+    eprintln!(
+        "note: {}x{} picture written to {path}",
+        picture.width(),
+        picture.height()
+    );
+    Ok(())
+}
+
+/// Append records to the history selected with `--history`
+fn append_history(args: &Args, records: &[history::Record]) -> Result<(), Error> {
+    let path = args.value("history").unwrap_or(DEFAULT_HISTORY);
+    history::append(path, &history::Run::now(), records)
+        .map_err(|e| Error::Failed(format!("error: {e}")))
+}
+
+fn bench(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(
+        args,
+        &[
+            "day",
+            "part",
+            "format",
+            "iterations",
+            "save",
+            "baseline",
+            "threshold",
+            "param",
+        ],
+        &["all"],
+    )?;
+    let part = part(&args)?;
+    let format = format(&args)?;
+    let iterations = args
+        .number::<u32>("iterations")?
+        .unwrap_or(DEFAULT_ITERATIONS);
+    if iterations == 0 {
+        return Err(Error::Usage(String::from(
+            "at least one iteration is needed",
+        )));
+    }
+    let threshold = args
+        .number::<f64>("threshold")?
+        .unwrap_or(DEFAULT_THRESHOLD);
+    let settings = settings(&args)?;
+    let baseline = match args.value("baseline") {
+        Some(path) => bench::read(path).map_err(|e| Error::Failed(format!("error: {e}")))?,
+        None => Vec::new(),
+    };
+
+    let mut records = Vec::new();
+    let mut regressions = 0;
+    for_each_target(&targets(&args)?, |day, input| {
+        let day_records = bench_day(day, &settings, input, part, iterations)?;
+        if format == Format::Text {
+            println!("{} ({iterations} iterations)", header(day));
+            println!(
+                "    {:<8} {:>10} {:>10} {:>10} {:>10} {:>8} {:>10} {:>10}",
+                "phase", "min", "median", "mean", "stddev", "allocs", "allocated", "peak"
+            );
+        }
+        for (record, usage) in day_records {
+            // Compare the median with the one of the baseline, if any
+            let change = baseline
+                .iter()
+                .find(|b| b.day == record.day && b.phase == record.phase)
+                .map(|previous| bench::change(&previous.stats, &record.stats));
+            let regression = change.is_some_and(|change| change > threshold);
+            if regression {
+                regressions += 1;
+            }
+
+            let stats = &record.stats;
+            match format {
+                Format::Text => {
+                    print!(
+                        "    {:<8} {:>10.1?} {:>10.1?} {:>10.1?} {:>10.1?} {:>8} {:>10} {:>10}",
+                        record.phase,
+                        stats.min,
+                        stats.median,
+                        stats.mean,
+                        stats.stddev,
+                        usage.allocations,
+                        alloc::size(usage.bytes),
+                        alloc::size(usage.peak)
+                    );
+                    if let Some(change) = change {
+                        print!(" {change:>+7.1}%");
+                    }
+                    if regression {
+                        print!(" regression");
+                    }
+                    println!();
+                }
+                Format::Json => {
+                    let mut object = json::Object::new()
+                        .value("day", record.day)
+                        .string("phase", &record.phase)
+                        .value("iterations", iterations)
+                        .value("min_ns", stats.min.as_nanos())
+                        .value("median_ns", stats.median.as_nanos())
+                        .value("mean_ns", stats.mean.as_nanos())
+                        .value("stddev_ns", stats.stddev.as_nanos())
+                        .value("allocations", usage.allocations)
+                        .value("allocated_bytes", usage.bytes)
+                        .value("peak_bytes", usage.peak);
+                    if let Some(change) = change {
+                        object = object
+                            .value("change_percent", format!("{change:.3}"))
+                            .value("regression", regression);
+                    }
+                    println!("{}", object.finish());
+                }
+            }
+
+            records.push(record);
+        }
+        Ok(())
+    })?;
+
+    if let Some(path) = args.value("save") {
+        bench::write(path, &records).map_err(|e| Error::Failed(format!("error: {e}")))?;
+    }
+    if regressions != 0 {
+        return Err(Error::Failed(format!(
+            "error: {regressions} phase(s) slower than the baseline by more than {threshold}%"
+        )));
+    }
+    Ok(())
+}
+
+fn summary(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(args, &["days", "jobs", "format", "history"], &["record"])?;
+    let format = format(&args)?;
+    let dir = match args.positional() {
+        [] => DEFAULT_INPUT_DIR,
+        [dir] => dir.as_str(),
+        _ => return Err(Error::Usage(String::from("too many arguments"))),
+    };
+    let jobs = match args.number::<usize>("jobs")? {
+        Some(0) => return Err(Error::Usage(String::from("at least one job is needed"))),
+        Some(jobs) => jobs,
+        None => std::thread::available_parallelism().map_or(1, usize::from),
+    };
+    let targets = inputs(dir, day_list(&args)?.into_iter());
+    if targets.is_empty() {
+        return Err(Error::Failed(format!("error: no input in {dir}")));
+    }
+
+    let start = Instant::now();
+    let outcomes = summary::solve_all(&targets, jobs);
+    let elapsed = start.elapsed();
+
+    let rows = targets
+        .iter()
+        .map(|(day, _)| *day)
+        .zip(&outcomes)
+        .collect::<Vec<_>>();
+    match format {
+        Format::Text => {
+            print!("{}", summary::table(&rows));
+            println!();
+            println!(
+                "{} day(s) in {elapsed:.1?} on {} thread(s)",
+                targets.len(),
+                jobs.min(targets.len())
+            );
+        }
+        Format::Json => {
+            for (day, outcome) in &rows {
+                println!("{}", outcome_record(day, outcome));
+            }
+        }
+    }
+
+    if args.switch("record") {
+        let mut records = Vec::new();
+        for (day, outcome) in &rows {
+            if let summary::Outcome::Solved(solved) = outcome {
+                for part in [1, 2] {
+                    records.push(history::Record {
+                        day: day.number,
+                        part,
+                        input: solved.input,
+                        elapsed: solved.parts[part as usize - 1],
+                        answer: solved.answers[part as usize - 1].clone(),
+                    });
+                }
+            }
+        }
+        append_history(&args, &records)?;
+    }
+
+    let mut failed = 0;
+    for (day, outcome) in &rows {
+        match outcome {
+            summary::Outcome::Solved(_) => continue,
+            summary::Outcome::Failed(message) => eprintln!("{message}"),
+            summary::Outcome::Panicked(message) => {
+                eprintln!("error: day {} panicked: {message}", day.number)
+            }
+        }
+        failed += 1;
+    }
+    if failed != 0 {
+        return Err(Error::Failed(format!("error: {failed} day(s) failed")));
+    }
+    Ok(())
+}
+
+fn history(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(args, &["day", "part", "history"], &[])?;
+    if !args.positional().is_empty() {
+        return Err(Error::Usage(String::from("too many arguments")));
+    }
+    let day = args.number::<u32>("day")?;
+    let part = part(&args)?;
+    let path = args.value("history").unwrap_or(DEFAULT_HISTORY);
+    let entries = history::read(path).map_err(|e| Error::Failed(format!("error: {e}")))?;
+
+    // Runs of each day, part and input in the order they were recorded
+    let mut groups = Vec::<((u32, u32, u64), Vec<&(history::Run, history::Record)>)>::new();
+    for entry in &entries {
+        let record = &entry.1;
+        if day.is_some_and(|day| day != record.day) || part.is_some_and(|p| p != record.part) {
+            continue;
+        }
+        let key = (record.day, record.part, record.input);
+        match groups.iter_mut().find(|(group, _)| *group == key) {
+            Some((_, runs)) => runs.push(entry),
+            None => groups.push((key, vec![entry])),
+        }
+    }
+    groups.sort_by_key(|((day, part, _), _)| (*day, *part));
+    let width = entries
+        .iter()
+        .map(|(run, _)| run.revision.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut changed = 0;
+    for ((day, part, input), runs) in &groups {
+        println!("Day {day} part {part}, input {input:016x}");
+        let mut previous: Option<&history::Record> = None;
+        for (run, record) in runs {
+            let elapsed = format!("{:.1?}", record.elapsed);
+            let change = previous.map_or(String::new(), |previous| {
+                let before = previous.elapsed.as_nanos() as f64;
+                let after = record.elapsed.as_nanos() as f64;
+                format!("{:+.1}%", (after - before) / before.max(1.0) * 100.0)
+            });
+            let flag = if previous.is_some_and(|previous| previous.answer != record.answer) {
+                changed += 1;
+                "  (changed)"
+            } else {
+                ""
+            };
+            println!(
+                "    {}  {:<width$}  {elapsed:>10}  {change:>8}  {}{flag}",
+                history::date(run.timestamp),
+                run.revision,
+                summary::cell(&record.answer)
+            );
+            previous = Some(record);
+        }
+    }
+
+    if changed != 0 {
+        return Err(Error::Failed(format!(
+            "error: {changed} answer(s) changed for the same input"
+        )));
+    }
+    Ok(())
+}
+
+fn params(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(args, &["day", "param"], &[])?;
+    if !args.positional().is_empty() {
+        return Err(Error::Usage(String::from("too many arguments")));
+    }
+    let day = day(&args)?;
+    let parser = day.configure(&args.values("param"))?;
+    if parser.values.is_empty() {
+        eprintln!("note: day {} has no parameters", day.number);
+    }
+    for (name, value) in &parser.values {
+        println!("{name}={value}");
+    }
+    Ok(())
+}
+
+fn watch(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(args, &["day", "part", "interval"], &[])?;
+    let day = day(&args)?;
+    let input = match args.positional() {
+        [input] if input == "-" => {
+            return Err(Error::Usage(String::from(
+                "the standard input cannot be watched",
+            )))
+        }
+        [input] => input,
+        [] => return Err(Error::Usage(String::from("missing input file"))),
+        _ => return Err(Error::Usage(String::from("too many arguments"))),
+    };
+    let part = part(&args)?;
+    let interval = args
+        .number::<u64>("interval")?
+        .unwrap_or(DEFAULT_WATCH_INTERVAL);
+    if !std::path::Path::new(input).is_file() {
+        return Err(Error::Failed(format!("error: no file {input}")));
+    }
+
+    println!("{}", header(day));
+    eprintln!("note: watching {input}, interrupt to stop");
+    let mut poller = watch::Poller::new(input);
+    let mut previous: Option<[String; 2]> = None;
+    loop {
+        if poller.changed() {
+            println!();
+            println!("{}  {input}", history::date(history::timestamp()));
+
+            // A panic is reported like a parse error, the next change may fix it
+            let hook = std::panic::take_hook();
+            std::panic::set_hook(Box::new(|_| ()));
+            let outcome = summary::solve(day, input);
+            std::panic::set_hook(hook);
+
+            match outcome {
+                summary::Outcome::Solved(solved) => {
+                    for index in 0..2 {
+                        if part.is_some_and(|part| part as usize != index + 1) {
+                            continue;
+                        }
+                        let before = previous.as_ref().map(|answers| answers[index].as_str());
+                        print!(
+                            "{}",
+                            watch::changes(
+                                &format!("part {}", index + 1),
+                                before,
+                                &solved.answers[index],
+                                solved.parts[index]
+                            )
+                        );
+                    }
+                    previous = Some(solved.answers);
+                }
+                summary::Outcome::Failed(message) => eprintln!("{message}"),
+                summary::Outcome::Panicked(message) => {
+                    eprintln!("error: day {} panicked: {message}", day.number)
+                }
+            }
+        }
+        std::thread::sleep(Duration::from_millis(interval));
+    }
+}
+
+fn lint(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(args, &["day"], &[])?;
+    let day = day(&args)?;
+    let input = match args.positional() {
+        [input] => input,
+        [] => return Err(Error::Usage(String::from("missing input file"))),
+        _ => return Err(Error::Usage(String::from("too many arguments"))),
+    };
+    let data = read(input)?;
+
+    // Each problem is followed by a blank line, which also separates the last one from the count
+    let errors = (day.lint)(&data);
+    let name = input::name(input);
+    let count = errors.len();
+    for error in errors {
+        eprintln!("{}\n", error.with_file(name));
+    }
+    match count {
+        0 => {
+            println!("{name}: no problem found");
+            Ok(())
+        }
+        count => Err(Error::Failed(format!(
+            "error: {count} problem(s) in {name}"
+        ))),
+    }
+}
+
+fn generate(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(args, &["day", "size", "seed"], &[])?;
+    if !args.positional().is_empty() {
+        return Err(Error::Usage(String::from("too many arguments")));
+    }
+    let day = day(&args)?;
+    let size = args.number::<usize>("size")?.unwrap_or(DEFAULT_SIZE);
+    if size == 0 {
+        return Err(Error::Usage(String::from("the size cannot be zero")));
+    }
+    let seed = seed(&args)?;
+
+    print!("{}", (day.generate)(&mut Rng::new(seed), size));
+    Ok(())
+}
+
+fn fuzz(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(args, &["day", "iterations", "seed", "corpus"], &[])?;
+    if !args.positional().is_empty() {
+        return Err(Error::Usage(String::from("too many arguments")));
+    }
+    let day = day(&args)?;
+    let iterations = args
+        .number::<u64>("iterations")?
+        .unwrap_or(DEFAULT_FUZZ_ITERATIONS);
+    let seed = seed(&args)?;
+    let dir = args
+        .value("corpus")
+        .map_or_else(|| format!("day{:02}", day.number), String::from);
+    if !std::path::Path::new(&dir).is_dir() {
+        return Err(Error::Failed(format!("error: no directory {dir}")));
+    }
+    let corpus = fuzz::corpus(&dir);
+    if corpus.is_empty() {
+        return Err(Error::Failed(format!("error: no input in {dir}")));
+    }
+
+    // Panics are expected, their message is reported along with the input
+    std::panic::set_hook(Box::new(|_| ()));
+    let result = fuzz::run(day.parse, &corpus, &mut Rng::new(seed), iterations);
+    drop(std::panic::take_hook());
+
+    let crash = match result {
+        Ok(()) => {
+            println!("{}", header(day));
+            println!("{iterations} inputs, no panic");
+            return Ok(());
+        }
+        Err(crash) => crash,
+    };
+    let path = std::path::Path::new(&dir)
+        .join("corpus")
+        .join(format!("crash-{seed}.txt"));
+    std::fs::create_dir_all(path.parent().unwrap())
+        .and_then(|()| std::fs::write(&path, &crash.input))
+        .map_err(|e| Error::Failed(format!("error: {}: {e}", path.display())))?;
+    Err(Error::Failed(format!(
+        "error: input saved to {} panics: {}",
+        path.display(),
+        crash.message
+    )))
+}
+
+fn animate(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(
+        args,
+        &["day", "part", "dir", "every", "limit", "scale"],
+        &[],
+    )?;
+    let (day, input, part, observe) = observed(&args)?;
+    let dir = args.value("dir").map_or_else(
+        || format!("{DEFAULT_FRAMES_DIR}/day{:02}", day.number),
+        String::from,
+    );
+
+    let failed = |e: String| Error::Failed(format!("error: {e}"));
+    let mut exporter = observe::Exporter::create(std::path::Path::new(&dir))
+        .map_err(failed)?
+        .every(args.number("every")?.unwrap_or(1))
+        .limit(args.number("limit")?.unwrap_or(DEFAULT_FRAME_LIMIT));
+    if let Some(scale) = args.number("scale")? {
+        exporter = exporter.pictures(scale);
+    }
+
+    let data = read(input)?;
+    let answer = observe(&data, part, &mut exporter)
+        .map_err(|e| Error::Failed(e.with_file(input::name(input)).to_string()))?;
+    let frames = exporter.finish().map_err(failed)?;
+    println!("{answer}");
+    eprintln!("note: {frames} frame(s) written to {dir}");
+    Ok(())
+}
+
+fn trace(args: &[String]) -> Result<(), Error> {
+    let args = Args::parse(args, &["day", "part", "output"], &[])?;
+    let (day, input, part, observe) = observed(&args)?;
+    let output = args.value("output").map_or_else(
+        || format!("{DEFAULT_TRACES_DIR}/day{:02}-part{part}.jsonl", day.number),
+        String::from,
+    );
+
+    let failed = |e: String| Error::Failed(format!("error: {e}"));
+    let mut tracer = observe::Tracer::create(std::path::Path::new(&output)).map_err(failed)?;
+    let data = read(input)?;
+    let answer = observe(&data, part, &mut tracer)
+        .map_err(|e| Error::Failed(e.with_file(input::name(input)).to_string()))?;
+    let events = tracer.finish().map_err(failed)?;
+    println!("{answer}");
+    eprintln!("note: {events} event(s) written to {output}");
+    Ok(())
+}
+
+/// Day, input and part to solve step by step, for `animate` and `trace`
+fn observed(args: &Args) -> Result<(&'static Day, &str, u32, ObserveFn), Error> {
+    let day = day(args)?;
+    let input = match args.positional() {
+        [input] => input,
+        [] => return Err(Error::Usage(String::from("missing input file"))),
+        _ => return Err(Error::Usage(String::from("too many arguments"))),
+    };
+    let part = part(args)?.unwrap_or(1);
+    let observe = day
+        .observe
+        .ok_or_else(|| Error::Failed(format!("error: day {} cannot be observed", day.number)))?;
+    Ok((day, input, part, observe))
+}
+
+/// Day selected with `--day`
+fn day(args: &Args) -> Result<&'static Day, Error> {
+    let number = args
+        .number::<u32>("day")?
+        .ok_or_else(|| String::from("missing `--day`"))?;
+    Ok(days::find(number).ok_or_else(|| format!("there is no day {number}"))?)
+}
+
+/// Seed given with `--seed`, or taken from the clock and printed on the standard error
+fn seed(args: &Args) -> Result<u64, Error> {
+    if let Some(seed) = args.number::<u64>("seed")? {
+        return Ok(seed);
+    }
+    let seed = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+    eprintln!("note: seed {seed}");
+    Ok(seed)
+}
+
+/// Part selected with `--part`, if any
+fn part(args: &Args) -> Result<Option<u32>, Error> {
+    let part = args.number::<u32>("part")?;
+    if let Some(part) = part {
+        if part != 1 && part != 2 {
+            return Err(Error::Usage(format!("there is no part {part}")));
+        }
+    }
+    Ok(part)
+}
+
+/// Parameters overridden with `--param`, for a single day
+fn settings(args: &Args) -> Result<Vec<String>, Error> {
+    let settings = args.values("param");
+    if !settings.is_empty() && args.switch("all") {
+        return Err(Error::Usage(String::from(
+            "`--param` needs a single day with `--day`",
+        )));
+    }
+    Ok(settings)
+}
+
+/// Output format selected with `--format`, text by default
+fn format(args: &Args) -> Result<Format, Error> {
+    match args.value("format") {
+        None | Some("text") => Ok(Format::Text),
+        Some("json") => Ok(Format::Json),
+        Some(format) => Err(Error::Usage(format!("unknown format `{format}`"))),
+    }
+}
+
+/// Days selected with `--day N INPUT` or `--all [DIR]`, along with their input
+fn targets(args: &Args) -> Result<Vec<(&'static Day, String)>, Error> {
+    if args.switch("all") {
+        if args.value("day").is_some() {
+            return Err(Error::Usage(String::from(
+                "`--all` and `--day` are exclusive",
+            )));
+        }
+        let dir = match args.positional() {
+            [] => DEFAULT_INPUT_DIR,
+            [dir] => dir.as_str(),
+            _ => return Err(Error::Usage(String::from("too many arguments"))),
+        };
+
+        return Ok(inputs(dir, days::DAYS.iter()));
+    }
+
+    let number = args
+        .number::<u32>("day")?
+        .ok_or_else(|| String::from("missing `--day` or `--all`"))?;
+    let day = days::find(number).ok_or_else(|| format!("there is no day {number}"))?;
+    match args.positional() {
+        [input] => Ok(vec![(day, input.clone())]),
+        [] => Err(Error::Usage(String::from("missing input file"))),
+        _ => Err(Error::Usage(String::from("too many arguments"))),
+    }
+}
+
+/// Days along with their input `DIR/dayNN.txt`, skipping the ones without input
+fn inputs(dir: &str, days: impl Iterator<Item = &'static Day>) -> Vec<(&'static Day, String)> {
+    let mut targets = Vec::new();
+    for day in days {
+        let input = format!("{dir}/day{:02}.txt", day.number);
+        if std::path::Path::new(&input).exists() {
+            targets.push((day, input));
+        } else {
+            eprintln!("note: skipping day {}, no input at {input}", day.number);
+        }
+    }
+    targets
+}
+
+/// Days listed with `--days` as numbers and ranges like `1,5,10-14`, all of them by default
+fn day_list(args: &Args) -> Result<Vec<&'static Day>, Error> {
+    let Some(list) = args.value("days") else {
+        return Ok(days::DAYS.iter().collect());
+    };
+    let number = |text: &str| {
+        let number = text
+            .parse::<u32>()
+            .map_err(|_| format!("invalid day `{text}` in `--days`"))?;
+        days::find(number).ok_or_else(|| format!("there is no day {number}"))
+    };
+
+    let mut selected = Vec::new();
+    for item in list.split(',') {
+        let (first, last) = match item.split_once('-') {
+            Some((first, last)) => (number(first)?, number(last)?),
+            None => (number(item)?, number(item)?),
+        };
+        if first.number > last.number {
+            return Err(Error::Usage(format!("empty range `{item}` in `--days`")));
+        }
+        for day in &days::DAYS[first.number as usize - 1..last.number as usize] {
+            if !selected
+                .iter()
+                .any(|selected: &&Day| selected.number == day.number)
+            {
+                selected.push(day);
+            }
+        }
+    }
+    selected.sort_by_key(|day| day.number);
+    Ok(selected)
+}
+
+/// Handle every target, a failing day does not prevent handling the others
+fn for_each_target(
+    targets: &[(&Day, String)],
+    mut f: impl FnMut(&Day, &str) -> Result<(), Error>,
+) -> Result<(), Error> {
+    if let [(day, input)] = targets {
+        return f(day, input);
+    }
+
+    let mut failed = 0;
+    for (day, input) in targets {
+        if let Err(Error::Failed(message) | Error::Usage(message)) = f(day, input) {
+            eprintln!("{message}");
+            failed += 1;
+        }
+    }
+    if failed != 0 {
+        return Err(Error::Failed(format!("error: {failed} day(s) failed")));
+    }
+    Ok(())
+}
+
+/// Read the input of a day, from a file or the standard input
+fn read(input: &str) -> Result<String, Error> {
+    input::read(input).map_err(|e| Error::Failed(e.to_string()))
+}
+
+fn header(day: &Day) -> String {
+    format!("Day {}: {}", day.number, day.title)
+}
+
+/// Parameters a day is solved with, like `relief_rounds=20 worry_rounds=500`
+fn params_text(parser: &Configured) -> String {
+    let values = parser
+        .values
+        .iter()
+        .map(|(name, value)| format!("{name}={value}"))
+        .collect::<Vec<_>>();
+    values.join(" ")
+}
+
+/// Solve the requested parts of a day with its parameters overridden by `settings`, printing
+/// answers along with the time it took and keeping them as records for the history
+fn solve(
+    day: &Day,
+    settings: &[String],
+    input: &str,
+    part: Option<u32>,
+    format: Format,
+    explain: Option<ExplainFn>,
+    records: &mut Vec<history::Record>,
+) -> Result<(), Error> {
+    let parser = day.configure(settings)?;
+    let data = read(input)?;
+    let start = Instant::now();
+    let (parsed, usage) = alloc::measure(|| parser.parse(&data));
+    let elapsed = start.elapsed();
+    let puzzle = parsed.map_err(|e| Error::Failed(e.with_file(input::name(input)).to_string()))?;
+
+    if format == Format::Text {
+        println!("{}", header(day));
+        if !settings.is_empty() {
+            println!("    {:<8} {}", "params", params_text(&parser));
+        }
+        print_phase("parse", None, elapsed, usage);
+    }
+
+    let mut answers = Vec::new();
+    if part.unwrap_or(1) == 1 {
+        let start = Instant::now();
+        let (answer, usage) = alloc::measure(|| puzzle.part1());
+        answers.push((1, answer, start.elapsed(), usage));
+    }
+    if part.unwrap_or(2) == 2 {
+        let start = Instant::now();
+        let (answer, usage) = alloc::measure(|| puzzle.part2());
+        answers.push((2, answer, start.elapsed(), usage));
+    }
+
+    for (part, answer, elapsed, usage) in answers {
+        let answer =
+            answer.map_err(|e| Error::Failed(e.with_file(input::name(input)).to_string()))?;
+        match format {
+            Format::Text => print_phase(&format!("part {part}"), Some(&answer), elapsed, usage),
+            Format::Json => println!("{}", answer_record(day, part, &answer, elapsed, usage)),
+        }
+        if let Some(explain) = explain {
+            // Explained on a parse of its own, out of the measures
+            let explanation = explain(&data, part)
+                .map_err(|e| Error::Failed(e.with_file(input::name(input)).to_string()))?;
+            match format {
+                Format::Text => {
+                    for line in explanation.lines() {
+                        println!("        {line}");
+                    }
+                }
+                Format::Json => println!(
+                    "{}",
+                    json::Object::new()
+                        .value("day", day.number)
+                        .value("part", part)
+                        .string("explanation", &explanation)
+                        .finish()
+                ),
+            }
+        }
+        records.push(history::Record {
+            day: day.number,
+            part,
+            input: history::hash(&data),
+            elapsed,
+            answer,
+        });
+    }
+
+    Ok(())
+}
+
+/// JSON record of an answer, answers are always strings so that pictures fit as well
+fn answer_record(
+    day: &Day,
+    part: u32,
+    answer: &str,
+    elapsed: Duration,
+    usage: alloc::Usage,
+) -> String {
+    json::Object::new()
+        .value("day", day.number)
+        .value("part", part)
+        .string("answer", answer)
+        .value("elapsed_ns", elapsed.as_nanos())
+        .value("allocations", usage.allocations)
+        .value("allocated_bytes", usage.bytes)
+        .value("peak_bytes", usage.peak)
+        .finish()
+}
+
+/// JSON record of the outcome of a day in a summary
+fn outcome_record(day: &Day, outcome: &summary::Outcome) -> String {
+    let record = json::Object::new().value("day", day.number);
+    match outcome {
+        summary::Outcome::Solved(solved) => record
+            .string("status", "solved")
+            .string("part1", &solved.answers[0])
+            .string("part2", &solved.answers[1])
+            .value("parse_ns", solved.parse.as_nanos())
+            .value("part1_ns", solved.parts[0].as_nanos())
+            .value("part2_ns", solved.parts[1].as_nanos()),
+        summary::Outcome::Failed(message) => {
+            record.string("status", "failed").string("error", message)
+        }
+        summary::Outcome::Panicked(message) => {
+            record.string("status", "panicked").string("error", message)
+        }
+    }
+    .finish()
+}
+
+fn print_phase(phase: &str, answer: Option<&str>, elapsed: Duration, usage: alloc::Usage) {
+    let cost = format!(
+        "({elapsed:.1?}, {} allocations, {}, peak {})",
+        usage.allocations,
+        alloc::size(usage.bytes),
+        alloc::size(usage.peak)
+    );
+    match answer {
+        // Multi-line answers (e.g. a picture) are printed below the phase
+        Some(answer) if answer.contains('\n') => {
+            println!("    {phase:<8} {cost}");
+            for line in answer.lines() {
+                println!("        {line}");
+            }
+        }
+        Some(answer) => println!("    {phase:<8} {answer} {cost}"),
+        None => println!("    {phase:<8} {cost}"),
+    }
+}
+
+/// Measure every phase of a day, parsing is measured along with the normalization of the input.
+/// The memory of each phase is counted on a run of its own, before the timed ones
+fn bench_day(
+    day: &Day,
+    settings: &[String],
+    input: &str,
+    part: Option<u32>,
+    iterations: u32,
+) -> Result<Vec<(bench::Record, alloc::Usage)>, Error> {
+    let parser = day.configure(settings)?;
+    let data = read(input)?;
+    let puzzle = parser
+        .parse(&data)
+        .map_err(|e| Error::Failed(e.with_file(input::name(input)).to_string()))?;
+
+    let mut phases = Vec::new();
+    phases.push((
+        "parse",
+        measure_phase(iterations, || {
+            black_box(parser.parse(black_box(&data)).ok());
+        }),
+    ));
+    if part.unwrap_or(1) == 1 {
+        let measured = measure_phase(iterations, || {
+            black_box(puzzle.part1().ok());
+        });
+        phases.push(("part1", measured));
+    }
+    if part.unwrap_or(2) == 2 {
+        let measured = measure_phase(iterations, || {
+            black_box(puzzle.part2().ok());
+        });
+        phases.push(("part2", measured));
+    }
+
+    Ok(phases
+        .into_iter()
+        .map(|(phase, (stats, usage))| {
+            let record = bench::Record {
+                day: day.number,
+                phase: phase.to_string(),
+                stats,
+            };
+            (record, usage)
+        })
+        .collect())
+}
+
+fn measure_phase(iterations: u32, mut phase: impl FnMut()) -> (bench::Stats, alloc::Usage) {
+    let ((), usage) = alloc::measure(&mut phase);
+    (bench::measure(iterations, phase), usage)
+}
+
+/// Answers are escaped into a single line record
+#[test]
+fn record() {
+    let usage = alloc::Usage {
+        allocations: 3,
+        bytes: 2048,
+        peak: 1024,
+    };
+    let record = answer_record(
+        &days::DAYS[9],
+        2,
+        "#.\n.#\n",
+        Duration::from_nanos(42),
+        usage,
+    );
+    assert_eq!(
+        record,
+        r##"{"day":10,"part":2,"answer":"#.\n.#\n","elapsed_ns":42,"allocations":3,"allocated_bytes":2048,"peak_bytes":1024}"##
+    );
+}
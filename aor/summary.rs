@@ -0,0 +1,200 @@
+//!
+//! Solving many days at once on a pool of threads, each day is parsed and solved on its own so
+//! that an error or a panic only fails this day
+//!
+
+use aoc_core::{fuzz, input, Day, ParseError};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Answers of a day and the time each phase took
+pub struct Solved {
+    /// Hash of the input, see [`crate::history::hash`]
+    pub input: u64,
+    pub answers: [String; 2],
+    pub parse: Duration,
+    pub parts: [Duration; 2],
+}
+
+impl Solved {
+    pub fn elapsed(&self) -> Duration {
+        self.parse + self.parts[0] + self.parts[1]
+    }
+}
+
+pub enum Outcome {
+    Solved(Solved),
+    /// The input cannot be read, parsed or answered, the message is a complete diagnostic
+    Failed(String),
+    Panicked(String),
+}
+
+/// Read, parse and solve the input of a day, catching any panic
+pub fn solve(day: &Day, input: &str) -> Outcome {
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        let data = input::read(input).map_err(|e| e.to_string())?;
+        let start = Instant::now();
+        let failed = |e: ParseError| e.with_file(input::name(input)).to_string();
+        let puzzle = (day.parse)(&data).map_err(failed)?;
+        let parse = start.elapsed();
+
+        let start = Instant::now();
+        let part1 = puzzle.part1().map_err(failed)?;
+        let elapsed = start.elapsed();
+        let start = Instant::now();
+        let part2 = puzzle.part2().map_err(failed)?;
+        Ok(Solved {
+            input: crate::history::hash(&data),
+            answers: [part1, part2],
+            parse,
+            parts: [elapsed, start.elapsed()],
+        })
+    }));
+    match result {
+        Ok(Ok(solved)) => Outcome::Solved(solved),
+        Ok(Err(message)) => Outcome::Failed(message),
+        Err(payload) => Outcome::Panicked(fuzz::panic_message(payload.as_ref())),
+    }
+}
+
+/// Solve every target on `jobs` threads, outcomes are in the order of the targets
+pub fn solve_all(targets: &[(&Day, String)], jobs: usize) -> Vec<Outcome> {
+    let next = AtomicUsize::new(0);
+    let outcomes = Mutex::new((0..targets.len()).map(|_| None).collect::<Vec<_>>());
+
+    // Panics are reported along with the day, not printed as they happen
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| ()));
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(targets.len()) {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some((day, input)) = targets.get(index) else {
+                    break;
+                };
+                let outcome = solve(day, input);
+                outcomes.lock().unwrap()[index] = Some(outcome);
+            });
+        }
+    });
+    panic::set_hook(hook);
+
+    outcomes
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(Option::unwrap)
+        .collect()
+}
+
+/// Answer as it fits in a table cell, pictures are summed up by their count of lines
+pub fn cell(answer: &str) -> String {
+    match answer.lines().count() {
+        0 | 1 => answer.to_string(),
+        lines => format!("[{lines} lines]"),
+    }
+}
+
+/// Table of the outcome of every day, with columns aligned on their widest cell
+pub fn table(outcomes: &[(&Day, &Outcome)]) -> String {
+    let mut rows = vec![[
+        String::from("Day"),
+        String::from("Title"),
+        String::from("Part 1"),
+        String::from("Part 2"),
+        String::from("Time"),
+    ]];
+    for (day, outcome) in outcomes {
+        let (part1, part2, time) = match outcome {
+            Outcome::Solved(solved) => (
+                cell(&solved.answers[0]),
+                cell(&solved.answers[1]),
+                format!("{:.1?}", solved.elapsed()),
+            ),
+            Outcome::Failed(_) => (String::from("error"), String::new(), String::new()),
+            Outcome::Panicked(_) => (String::from("panic"), String::new(), String::new()),
+        };
+        rows.push([
+            day.number.to_string(),
+            day.title.to_string(),
+            part1,
+            part2,
+            time,
+        ]);
+    }
+
+    let mut widths = [0; 5];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let mut table = String::new();
+    for row in &rows {
+        // Numbers are aligned to the right, text to the left
+        let line = format!(
+            "{:>w0$}  {:<w1$}  {:<w2$}  {:<w3$}  {:>w4$}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            row[4],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2],
+            w3 = widths[3],
+            w4 = widths[4],
+        );
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    table
+}
+
+/// Columns are aligned and failures do not prevent the other days from being listed
+#[test]
+fn aligned() {
+    let days = [
+        Day {
+            number: 1,
+            title: "Calorie Counting",
+            ..day01::DAY
+        },
+        Day {
+            number: 10,
+            title: "Cathode-Ray Tube",
+            ..day01::DAY
+        },
+        Day {
+            number: 11,
+            title: "Monkey",
+            ..day01::DAY
+        },
+    ];
+    let solved = |answers: [&str; 2]| {
+        Outcome::Solved(Solved {
+            input: 0,
+            answers: answers.map(String::from),
+            parse: Duration::from_micros(100),
+            parts: [Duration::from_micros(50), Duration::from_micros(1500)],
+        })
+    };
+    let outcomes = [
+        solved(["24000", "45000"]),
+        solved(["13140", "##..\n..##\n"]),
+        Outcome::Panicked(String::from("index out of bounds")),
+    ];
+    let rows = days.iter().zip(&outcomes).collect::<Vec<_>>();
+    assert_eq!(
+        table(&rows),
+        "\
+Day  Title             Part 1  Part 2      Time
+  1  Calorie Counting  24000   45000      1.6ms
+ 10  Cathode-Ray Tube  13140   [2 lines]  1.6ms
+ 11  Monkey            panic
+"
+    );
+}
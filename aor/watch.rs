@@ -0,0 +1,132 @@
+//!
+//! Watching an input file to solve it again each time it is saved, by polling its modification
+//! time so that no platform notifier is needed
+//!
+
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+/// File polled for changes of its modification time or length
+pub struct Poller {
+    path: PathBuf,
+    stamp: Option<(SystemTime, u64)>,
+}
+
+impl Poller {
+    pub fn new(path: &str) -> Self {
+        Self {
+            path: PathBuf::from(path),
+            stamp: None,
+        }
+    }
+
+    /// Whether the file changed since the last poll, it always did on the first one. A file
+    /// missing for a while, as some editors replace it when saving, is not a change
+    pub fn changed(&mut self) -> bool {
+        let stamp = std::fs::metadata(&self.path)
+            .and_then(|metadata| Ok((metadata.modified()?, metadata.len())))
+            .ok();
+        if stamp.is_none() || stamp == self.stamp {
+            return false;
+        }
+        self.stamp = stamp;
+        true
+    }
+}
+
+/// Answer to a part along with how it changed since the previous run, if any. Lines of a
+/// picture are marked with `-` and `+` where they changed
+pub fn changes(phase: &str, previous: Option<&str>, answer: &str, elapsed: Duration) -> String {
+    let multiline = answer.contains('\n') || previous.is_some_and(|p| p.contains('\n'));
+    let mut text = match previous {
+        Some(previous) if previous == answer && !multiline => {
+            format!("    {phase:<8} {answer} (unchanged, {elapsed:.1?})\n")
+        }
+        Some(previous) if !multiline => {
+            format!("    {phase:<8} {previous} -> {answer} ({elapsed:.1?})\n")
+        }
+        None if !multiline => format!("    {phase:<8} {answer} ({elapsed:.1?})\n"),
+        Some(previous) if previous == answer => {
+            format!("    {phase:<8} (unchanged, {elapsed:.1?})\n")
+        }
+        Some(_) => format!("    {phase:<8} (changed, {elapsed:.1?})\n"),
+        None => format!("    {phase:<8} ({elapsed:.1?})\n"),
+    };
+    if !multiline {
+        return text;
+    }
+
+    // Pictures keep their size, their lines are compared one by one
+    let before = previous.map_or_else(Vec::new, |previous| previous.lines().collect());
+    let after = answer.lines().collect::<Vec<_>>();
+    for index in 0..before.len().max(after.len()) {
+        match (before.get(index), after.get(index)) {
+            (old, Some(new)) if previous.is_none() || old == Some(new) => {
+                text.push_str(&format!("          {new}\n"));
+            }
+            (old, new) => {
+                if let Some(old) = old {
+                    text.push_str(&format!("        - {old}\n"));
+                }
+                if let Some(new) = new {
+                    text.push_str(&format!("        + {new}\n"));
+                }
+            }
+        }
+    }
+    text
+}
+
+/// Single line answers are shown along with the previous one when they changed
+#[test]
+fn numbers() {
+    let elapsed = Duration::from_micros(1500);
+    assert_eq!(
+        changes("part 1", None, "10605", elapsed),
+        "    part 1   10605 (1.5ms)\n"
+    );
+    assert_eq!(
+        changes("part 1", Some("10605"), "10605", elapsed),
+        "    part 1   10605 (unchanged, 1.5ms)\n"
+    );
+    assert_eq!(
+        changes("part 2", Some("10605"), "99", elapsed),
+        "    part 2   10605 -> 99 (1.5ms)\n"
+    );
+}
+
+/// Pictures are compared line by line
+#[test]
+fn pictures() {
+    let elapsed = Duration::from_micros(1500);
+    assert_eq!(
+        changes("part 2", None, "#.\n.#\n", elapsed),
+        "    part 2   (1.5ms)\n          #.\n          .#\n"
+    );
+    assert_eq!(
+        changes("part 2", Some("#.\n.#\n"), "#.\n##\n", elapsed),
+        "    part 2   (changed, 1.5ms)\n          #.\n        - .#\n        + ##\n"
+    );
+    assert_eq!(
+        changes("part 2", Some("#.\n"), "#.\n##\n", elapsed),
+        "    part 2   (changed, 1.5ms)\n          #.\n        + ##\n"
+    );
+}
+
+/// Files are reported as changed once, then each time they are written again
+#[test]
+fn polled() {
+    let path = std::env::temp_dir().join(format!("aor-watch-{}.txt", std::process::id()));
+    let mut poller = Poller::new(path.to_str().unwrap());
+    assert!(!poller.changed());
+
+    std::fs::write(&path, "1\n").unwrap();
+    assert!(poller.changed());
+    assert!(!poller.changed());
+
+    // The length changes even when the modification time is too coarse to
+    std::fs::write(&path, "12\n").unwrap();
+    assert!(poller.changed());
+    std::fs::remove_file(&path).unwrap();
+    assert!(!poller.changed());
+}
@@ -2,73 +2,302 @@
 //! [Day 1: Calorie Counting](https://adventofcode.com/2022/day/1)
 //!
 
-struct Puzzle {
+#![warn(missing_docs)]
+
+use aoc_core::observe::event;
+use aoc_core::params::{self, Value};
+use aoc_core::{
+    parse, Configure, Day, Explain, Generate, Observe, Observer, ParseError, Reference, Rng,
+    Solution,
+};
+use std::str::FromStr;
+
+/// Constants of the puzzle
+#[derive(Clone, Debug)]
+pub struct Params {
+    /// Count of elves carrying the most whose calories are added up in the second part
+    pub top: usize,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self { top: 3 }
+    }
+}
+
+impl params::Params for Params {
+    fn fields(&mut self) -> Vec<(&'static str, &mut dyn Value)> {
+        vec![("top", &mut self.top)]
+    }
+}
+
+/// Calories carried by each elf, the largest totals first
+pub struct Puzzle {
     calories: Vec<usize>,
+    /// Number of each elf in the input, from 1, in the order of `calories`
+    elves: Vec<usize>,
+    params: Params,
+}
+
+/// Elves carrying the most calories, as found by [`Puzzle::top`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Top {
+    /// Number of each elf in the input, from 1, along with its calories, from the largest
+    pub elves: Vec<(usize, usize)>,
+    /// Calories carried by these elves together
+    pub total: usize,
+}
+
+/// The day as run by `aor`
+pub const DAY: Day = Day::new::<Puzzle>(1, "Calorie Counting")
+    .with_observe::<Puzzle>()
+    .with_params::<Puzzle>()
+    .with_explain::<Puzzle>();
+
+impl Solution for Puzzle {
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Self::parse_with(input, Params::default())
+    }
+
+    fn part1(&self) -> usize {
+        self.top(1, &mut ()).total
+    }
+
+    fn part2(&self) -> usize {
+        self.top(self.params.top, &mut ()).total
+    }
+}
+
+impl Configure for Puzzle {
+    type Params = Params;
+
+    fn parse_with(input: &str, params: Params) -> Result<Self, ParseError> {
+        let mut calories = Vec::new();
+
+        // Keep the sum of every total in range, so that no sum of totals overflows
+        let mut grand_total = 0usize;
+        for elf in parse::blocks(input)? {
+            let mut total = 0;
+            for line in elf {
+                let mut scanner = line.scan();
+                let start = scanner.clone();
+                let value = scanner.number::<usize>()?;
+                grand_total = grand_total
+                    .checked_add(value)
+                    .ok_or_else(|| start.error("calories adding up to a `usize`"))?;
+                total += value;
+                scanner.end()?;
+            }
+            calories.push(total);
+        }
+        if calories.is_empty() {
+            return Err(parse::end_of_input(input, "calories of an elf"));
+        }
+
+        // Reverse sort to have to most significant values first, elves carrying as much keep
+        // their order
+        let mut ranking = calories.into_iter().zip(1..).collect::<Vec<_>>();
+        ranking.sort_by_key(|&(calories, _)| std::cmp::Reverse(calories));
+        let (calories, elves) = ranking.into_iter().unzip();
+
+        Ok(Self {
+            calories,
+            elves,
+            params,
+        })
+    }
 }
 
 impl Puzzle {
-    fn new() -> Self {
-        Self {
-            calories: Vec::new(),
+    /// Total calories carried by each elf, from the largest
+    pub fn calories(&self) -> &[usize] {
+        &self.calories
+    }
+
+    /// The `count` elves carrying the most and their calories
+    pub fn top(&self, count: usize, observer: &mut dyn Observer) -> Top {
+        let mut top = Top {
+            elves: Vec::new(),
+            total: 0,
+        };
+        for (rank, (&calories, &elf)) in self
+            .calories
+            .iter()
+            .zip(&self.elves)
+            .take(count)
+            .enumerate()
+        {
+            top.total += calories;
+            top.elves.push((elf, calories));
+            observer.trace(|| {
+                event("Top")
+                    .value("rank", rank + 1)
+                    .value("calories", calories)
+            });
         }
+        top
     }
+}
 
-    fn configure(&mut self, path: &str) {
-        let mut data = std::fs::read_to_string(path).unwrap();
-        data.pop();
-        self.calories = data
-            .split("\n\n")
-            .map(|x| {
-                x.split('\n')
-                    .map(|y| y.parse::<usize>().unwrap())
-                    .sum::<usize>()
-            })
-            .collect::<Vec<_>>();
-
-        // Reverse sort to have to most significant values first
-        self.calories.sort_by(|a, b| b.cmp(a));
+impl Observe for Puzzle {
+    /// A `Top` event for each elf counted, from the one carrying the most
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError> {
+        let puzzle = Self::from_input(input)?;
+        let count = if part == 1 { 1 } else { puzzle.params.top };
+        Ok(puzzle.top(count, observer).total.to_string())
     }
+}
 
-    fn part1(&self) -> usize {
-        self.calories[0]
+impl Explain for Puzzle {
+    /// The elves counted, from the one carrying the most
+    fn explain(&self, part: u32) -> String {
+        let count = if part == 1 { 1 } else { self.params.top };
+        let top = self.top(count, &mut ());
+        let mut text = String::new();
+        for (rank, (elf, calories)) in top.elves.iter().enumerate() {
+            text.push_str(&format!("#{} elf {elf}: {calories} calories\n", rank + 1));
+        }
+        text
     }
+}
 
-    fn part2(&self) -> usize {
-        self.calories[0..3].iter().sum::<usize>()
+impl Generate for Puzzle {
+    /// Calories carried by `size` elves
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut input = String::new();
+        for elf in 0..size {
+            if elf != 0 {
+                input.push('\n');
+            }
+            for _ in 0..rng.range(1..=8) {
+                input.push_str(&format!("{}\n", rng.range(1000..=20_000)));
+            }
+        }
+        input
+    }
+}
+
+impl Reference for Puzzle {
+    fn reference_part1(&self) -> usize {
+        *self.calories.iter().max().unwrap()
     }
+
+    fn reference_part2(&self) -> usize {
+        // Pick the largest remaining total as many times as there are elves counted
+        let mut calories = self.calories.clone();
+        let mut result = 0;
+        for _ in 0..self.params.top {
+            let Some((index, total)) = calories.iter().enumerate().max_by_key(|(_, &c)| c) else {
+                break;
+            };
+            result += total;
+            calories.remove(index);
+        }
+        result
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s)
+    }
+}
+
+/// Test every example input against its manifest
+#[test]
+fn examples() {
+    aoc_core::testing::check_examples::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test inputs of the generator
+#[test]
+fn generated() {
+    aoc_core::testing::check_generated::<Puzzle>();
 }
 
-/// Test from puzzle input
+/// Compare with the reference solution on generated inputs
 #[test]
-fn test01() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test01.txt");
+fn reference() {
+    aoc_core::testing::check_reference::<Puzzle>();
+}
+
+/// Test mutated inputs never panic
+#[test]
+fn fuzz() {
+    aoc_core::testing::check_fuzz::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test the top three elves carry at least as much as the top one
+#[test]
+fn properties() {
+    aoc_core::testing::check_property::<Puzzle>("top-three", |puzzle| {
+        let (top, top_three) = (puzzle.part1(), puzzle.part2());
+        if top_three < top {
+            return Err(format!(
+                "the top three carry {top_three}, the top one {top}"
+            ));
+        }
+        Ok(())
+    });
+}
+
+/// Test puzzle input with Windows line endings and without final newline
+#[test]
+fn crlf() {
+    let input = std::fs::read_to_string("test01.txt").unwrap();
+    let puzzle = input
+        .trim_end()
+        .replace('\n', "\r\n")
+        .parse::<Puzzle>()
+        .unwrap();
     assert_eq!(puzzle.part1(), 24000);
     assert_eq!(puzzle.part2(), 45000);
 }
 
-/// Test from user input
+/// Test malformed input
+#[test]
+fn malformed() {
+    let error = "1000\n2000\n\n3x00\n".parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (4, 2));
+
+    let input = format!("1\n\n{}\n", usize::MAX);
+    let error = input.parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (3, 1));
+}
+
+/// Test the elves counted are numbered in the order of the input
 #[test]
-fn test02() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test02.txt");
-    assert_eq!(puzzle.part1(), 69310);
-    assert_eq!(puzzle.part2(), 206104);
+fn explained() {
+    let puzzle = std::fs::read_to_string("test01.txt")
+        .unwrap()
+        .parse::<Puzzle>()
+        .unwrap();
+    let top = puzzle.top(3, &mut ());
+    assert_eq!(top.elves, [(4, 24000), (3, 11000), (5, 10000)]);
+    assert_eq!(top.total, 45000);
+    assert_eq!(puzzle.explain(1), "#1 elf 4: 24000 calories\n");
 }
 
-/// Test from user input
+/// Test input from a reader
 #[test]
-fn test03() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test03.txt");
-    assert_eq!(puzzle.part1(), 72070);
-    assert_eq!(puzzle.part2(), 211805);
-}
-
-fn main() {
-    let mut puzzle = Puzzle::new();
-    let input = std::env::args().nth(1).expect("No input file");
-    puzzle.configure(&input);
-    println!("{}", puzzle.part1());
-    println!("{}", puzzle.part2());
+fn reader() {
+    let puzzle = Puzzle::from_reader("100\n\n200\n300\n".as_bytes()).unwrap();
+    assert_eq!(puzzle.part1(), 500);
+    assert_eq!(puzzle.part2(), 600);
+}
+
+/// Test the count of elves of the second part can be changed
+#[test]
+fn params() {
+    let input = std::fs::read_to_string("test01.txt").unwrap();
+    for (top, expected) in [(1, 24000), (2, 35000), (10, 55000)] {
+        let puzzle = Puzzle::from_input_with(&input, Params { top }).unwrap();
+        assert_eq!(puzzle.part2(), expected);
+        assert_eq!(puzzle.reference_part2(), expected);
+    }
 }
@@ -2,52 +2,105 @@
 //! [Day 2: Rock Paper Scissors](https://adventofcode.com/2022/day/2)
 //!
 
-const VALUE_ROCK: u32 = 1;
-const VALUE_PAPER: u32 = 2;
-const VALUE_SCISSORS: u32 = 3;
+#![warn(missing_docs)]
+
+use aoc_core::observe::event;
+use aoc_core::parse::{self, Issues, Line};
+use aoc_core::{Day, Generate, Lint, Observe, Observer, ParseError, Reference, Rng, Solution};
+use std::str::FromStr;
+
+/// Shapes are numbered by the score they are worth, rock beats scissors
+pub const VALUE_ROCK: u32 = 1;
+/// Paper beats rock
+pub const VALUE_PAPER: u32 = 2;
+/// Scissors beat paper
+pub const VALUE_SCISSORS: u32 = 3;
 
 const SHOULD_LOSE: u32 = 1;
 const SHOULD_DRAW: u32 = 2;
 
-const ROUND_OUTCOME_DRAW: u32 = 3;
-const ROUND_OUTCOME_WIN: u32 = 6;
+/// Score of a round ending in a draw, a lost round is worth nothing
+pub const ROUND_OUTCOME_DRAW: u32 = 3;
+/// Score of a round won
+pub const ROUND_OUTCOME_WIN: u32 = 6;
 
-struct Puzzle {
+/// Strategy guide of the Rock Paper Scissors tournament
+pub struct Puzzle {
     guide: Vec<(u32, u32)>,
 }
 
+/// The day as run by `aor`
+pub const DAY: Day = Day::new::<Puzzle>(2, "Rock Paper Scissors")
+    .with_observe::<Puzzle>()
+    .with_lint::<Puzzle>();
+
+impl Solution for Puzzle {
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut issues = Issues::default();
+        let puzzle = Self::read(input, &mut issues);
+        issues.first(puzzle)
+    }
+
+    fn part1(&self) -> u32 {
+        self.play_shapes(&mut ())
+    }
+
+    fn part2(&self) -> u32 {
+        self.play_outcomes(&mut ())
+    }
+}
+
+impl Lint for Puzzle {
+    fn lint(input: &str) -> Vec<ParseError> {
+        let mut issues = Issues::default();
+        Self::read(input, &mut issues);
+        issues.errors()
+    }
+}
+
 impl Puzzle {
-    fn new() -> Self {
-        Self { guide: Vec::new() }
+    /// Parse the guide, going on after any round in error
+    fn read(input: &str, issues: &mut Issues) -> Option<Self> {
+        let guide = parse::lines(input)
+            .iter()
+            .filter_map(|line| issues.check(Self::parse_round(line)))
+            .collect();
+        Some(Self { guide })
     }
 
-    fn configure(&mut self, path: &str) {
-        let data = std::fs::read_to_string(path).unwrap();
-        let mut lines = data.split('\n').collect::<Vec<_>>();
-        lines.pop();
-
-        for strategy in lines {
-            let shapes = strategy.split(' ').collect::<Vec<_>>();
-            let opponent = match shapes[0].parse::<char>().unwrap() {
-                'A' => VALUE_ROCK,
-                'B' => VALUE_PAPER,
-                'C' => VALUE_SCISSORS,
-                _ => panic!("bad input"),
-            };
-            let you = match shapes[1].parse::<char>().unwrap() {
-                'X' => 1,
-                'Y' => 2,
-                'Z' => 3,
-                _ => panic!("bad input"),
-            };
-            self.guide.push((opponent, you));
-        }
+    /// Parse a round, like `A Y`
+    fn parse_round(line: &Line) -> Result<(u32, u32), ParseError> {
+        let mut scanner = line.scan();
+        let opponent = match scanner.one_of(&['A', 'B', 'C'])? {
+            'A' => VALUE_ROCK,
+            'B' => VALUE_PAPER,
+            _ => VALUE_SCISSORS,
+        };
+        scanner.tag(" ")?;
+        let you = match scanner.one_of(&['X', 'Y', 'Z'])? {
+            'X' => 1,
+            'Y' => 2,
+            _ => 3,
+        };
+        scanner.end()?;
+        Ok((opponent, you))
     }
 
-    fn part1(&self) -> u32 {
+    /// Rounds of the guide: the shape played by the opponent, see [`VALUE_ROCK`], and the second
+    /// column numbered from 1 for `X` to 3 for `Z`
+    pub fn guide(&self) -> &[(u32, u32)] {
+        &self.guide
+    }
+
+    /// Total score playing the second column as shapes
+    pub fn play_shapes(&self, observer: &mut dyn Observer) -> u32 {
         let mut result = 0;
-        for strategy in &self.guide {
+        for (round, strategy) in self.guide.iter().enumerate() {
             let (opponent, you) = *strategy;
+            let before = result;
 
             // Always win what has been played
             result += you;
@@ -58,14 +111,17 @@ impl Puzzle {
             } else if opponent == you {
                 result += ROUND_OUTCOME_DRAW;
             }
+            trace_round(observer, round, *strategy, result - before);
         }
         result
     }
 
-    fn part2(&self) -> u32 {
+    /// Total score playing for the outcomes of the second column
+    pub fn play_outcomes(&self, observer: &mut dyn Observer) -> u32 {
         let mut result = 0;
-        for strategy in &self.guide {
+        for (round, strategy) in self.guide.iter().enumerate() {
             let (opponent, you) = *strategy;
+            let before = result;
 
             if you == SHOULD_LOSE {
                 if opponent == VALUE_ROCK {
@@ -85,42 +141,150 @@ impl Puzzle {
                     result += opponent + 1;
                 }
             }
+            trace_round(observer, round, *strategy, result - before);
         }
         result
     }
 }
 
-/// Test from puzzle input
+/// `Round` event with the columns of the guide as written, `A` to `C` and `X` to `Z`
+fn trace_round(observer: &mut dyn Observer, round: usize, (opponent, you): (u32, u32), score: u32) {
+    observer.trace(|| {
+        let letter = |first: u8, value: u32| char::from(first + value as u8 - 1).to_string();
+        event("Round")
+            .value("round", round + 1)
+            .string("opponent", &letter(b'A', opponent))
+            .string("you", &letter(b'X', you))
+            .value("score", score)
+    });
+}
+
+impl Observe for Puzzle {
+    /// A `Round` event with the score of each round
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError> {
+        let puzzle = Self::from_input(input)?;
+        Ok(match part {
+            1 => puzzle.play_shapes(observer),
+            _ => puzzle.play_outcomes(observer),
+        }
+        .to_string())
+    }
+}
+
+impl Generate for Puzzle {
+    /// Strategy guide of `size` rounds
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut input = String::new();
+        for _ in 0..size {
+            let opponent = rng.pick(&['A', 'B', 'C']);
+            let you = rng.pick(&['X', 'Y', 'Z']);
+            input.push_str(&format!("{opponent} {you}\n"));
+        }
+        input
+    }
+}
+
+/// Outcome of a round from its shapes, spelled out for every win
+pub fn round_outcome(opponent: u32, you: u32) -> u32 {
+    match (opponent, you) {
+        (VALUE_ROCK, VALUE_PAPER)
+        | (VALUE_PAPER, VALUE_SCISSORS)
+        | (VALUE_SCISSORS, VALUE_ROCK) => ROUND_OUTCOME_WIN,
+        _ if opponent == you => ROUND_OUTCOME_DRAW,
+        _ => 0,
+    }
+}
+
+impl Reference for Puzzle {
+    fn reference_part1(&self) -> u32 {
+        self.guide
+            .iter()
+            .map(|&(opponent, you)| you + round_outcome(opponent, you))
+            .sum()
+    }
+
+    fn reference_part2(&self) -> u32 {
+        self.guide
+            .iter()
+            .map(|&(opponent, end)| {
+                let outcome = (end - SHOULD_LOSE) * ROUND_OUTCOME_DRAW;
+
+                // Try every shape until one ends the round as expected
+                let you = [VALUE_ROCK, VALUE_PAPER, VALUE_SCISSORS]
+                    .into_iter()
+                    .find(|&you| round_outcome(opponent, you) == outcome)
+                    .unwrap();
+                you + outcome
+            })
+            .sum()
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s)
+    }
+}
+
+/// Test every example input against its manifest
+#[test]
+fn examples() {
+    aoc_core::testing::check_examples::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test inputs of the generator
+#[test]
+fn generated() {
+    aoc_core::testing::check_generated::<Puzzle>();
+}
+
+/// Compare with the reference solution on generated inputs
 #[test]
-fn test01() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test01.txt");
-    assert_eq!(puzzle.part1(), 15);
-    assert_eq!(puzzle.part2(), 12);
+fn reference() {
+    aoc_core::testing::check_reference::<Puzzle>();
 }
 
-/// Test from user input
+/// Test mutated inputs never panic
 #[test]
-fn test02() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test02.txt");
-    assert_eq!(puzzle.part1(), 15337);
-    assert_eq!(puzzle.part2(), 11696);
+fn fuzz() {
+    aoc_core::testing::check_fuzz::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test malformed input
+#[test]
+fn malformed() {
+    let error = "A Y\nB W\n".parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (2, 3));
+}
+
+/// Test every malformed round is reported
+#[test]
+fn linted() {
+    aoc_core::testing::check_lint::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+
+    let errors = Puzzle::lint("A Y\nB W\nD X\nC Z\n");
+    let positions = errors
+        .iter()
+        .map(|e| (e.line, e.column))
+        .collect::<Vec<_>>();
+    assert_eq!(positions, [(2, 3), (3, 1)]);
 }
 
-/// Test from user input
+/// Test each round is traced with its score
 #[test]
-fn test03() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test03.txt");
-    assert_eq!(puzzle.part1(), 12156);
-    assert_eq!(puzzle.part2(), 10835);
-}
-
-fn main() {
-    let mut puzzle = Puzzle::new();
-    let input = std::env::args().nth(1).expect("No input file");
-    puzzle.configure(&input);
-    println!("{}", puzzle.part1());
-    println!("{}", puzzle.part2());
+fn traced() {
+    let mut recorder = aoc_core::observe::Recorder::default();
+    assert_eq!(
+        Puzzle::observe("A Y\nC Z\n", 2, &mut recorder).unwrap(),
+        "11"
+    );
+    assert_eq!(
+        recorder.events,
+        [
+            r#"{"event":"Round","round":1,"opponent":"A","you":"Y","score":4}"#,
+            r#"{"event":"Round","round":2,"opponent":"C","you":"Z","score":7}"#
+        ]
+    );
 }
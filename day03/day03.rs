@@ -2,38 +2,112 @@
 //! [Day 3: Rucksack Reorganization](https://adventofcode.com/2022/day/3)
 //!
 
-struct Puzzle {
+#![warn(missing_docs)]
+
+use aoc_core::json;
+use aoc_core::observe::event;
+use aoc_core::{
+    parse, Day, Explain, Generate, Observe, Observer, ParseError, Reference, Rng, Solution,
+};
+use std::str::FromStr;
+
+/// Rucksacks of the elves, with a letter per item
+pub struct Puzzle {
     rucksacks: Vec<String>,
 }
 
-impl Puzzle {
-    fn new() -> Self {
-        Self {
-            rucksacks: Vec::new(),
-        }
-    }
+/// Item found in a rucksack or in every rucksack of a group
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Found {
+    /// Number of the rucksack or of the group, from 1
+    pub holder: usize,
+    /// Letter of the item
+    pub item: char,
+    /// Priority of the item, see [`Puzzle::char_to_priority`]
+    pub priority: u32,
+}
 
-    fn char_to_priority(c: char) -> u32 {
+/// The day as run by `aor`
+pub const DAY: Day = Day::new::<Puzzle>(3, "Rucksack Reorganization")
+    .with_observe::<Puzzle>()
+    .with_explain::<Puzzle>();
+
+impl Puzzle {
+    /// Priority of an item, from 1 for `a` to 52 for `Z`
+    pub fn char_to_priority(c: char) -> u32 {
         match c {
             'A'..='Z' => 27 + u32::from(c) - u32::from('A'),
             _ => 1 + u32::from(c) - u32::from('a'),
         }
     }
+}
+
+impl Solution for Puzzle {
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let lines = parse::lines(input);
 
-    fn configure(&mut self, path: &str) {
-        let data = std::fs::read_to_string(path).unwrap();
-        let mut lines = data.split('\n').collect::<Vec<_>>();
-        lines.pop();
-        self.rucksacks = lines.iter().map(std::string::ToString::to_string).collect();
+        let mut rucksacks = Vec::new();
+        for line in &lines {
+            let mut scanner = line.scan();
+            while !scanner.is_end() {
+                if !scanner.peek().unwrap().is_ascii_alphabetic() {
+                    return Err(scanner.error("an item letter"));
+                }
+                scanner.next_char();
+            }
+            // Both compartments hold the same count of items
+            if line.text.len() % 2 != 0 {
+                return Err(line.error_at_end("an even count of items"));
+            }
+            rucksacks.push(line.text.to_string());
+        }
+
+        // Rucksacks are carried by groups of three elves
+        if rucksacks.len() % 3 != 0 {
+            return Err(parse::end_of_input(input, "a group of three rucksacks"));
+        }
+
+        Ok(Self { rucksacks })
     }
 
     fn part1(&self) -> u32 {
-        let mut result = 0;
-        for rucksack in &self.rucksacks {
+        self.shared(&mut ())
+            .iter()
+            .map(|found| found.priority)
+            .sum()
+    }
+
+    fn part2(&self) -> u32 {
+        self.badges(&mut ())
+            .iter()
+            .map(|found| found.priority)
+            .sum()
+    }
+}
+
+impl Puzzle {
+    /// Items of each rucksack, both of its compartments one after the other
+    pub fn rucksacks(&self) -> &[String] {
+        &self.rucksacks
+    }
+
+    /// Item in both compartments of each rucksack, the first part adds up their priorities
+    pub fn shared(&self, observer: &mut dyn Observer) -> Vec<Found> {
+        let mut result = Vec::new();
+        for (index, rucksack) in self.rucksacks.iter().enumerate() {
             let (first_compartment, second_compartment) = rucksack.split_at(rucksack.len() / 2);
             for c in first_compartment.chars() {
                 if second_compartment.contains(c) {
-                    result += Puzzle::char_to_priority(c);
+                    let priority = Puzzle::char_to_priority(c);
+                    result.push(Found {
+                        holder: index + 1,
+                        item: c,
+                        priority,
+                    });
+                    observer.trace(|| item_event("Shared", "rucksack", index, c, priority));
                     break;
                 }
             }
@@ -41,18 +115,25 @@ impl Puzzle {
         result
     }
 
-    fn part2(&self) -> u32 {
-        let mut result = 0;
+    /// Item carried by the three elves of each group, the second part adds up their priorities
+    pub fn badges(&self, observer: &mut dyn Observer) -> Vec<Found> {
+        let mut result = Vec::new();
 
         // Iterate over rucksacks by triples
-        for slice in self.rucksacks.chunks(3) {
+        for (index, slice) in self.rucksacks.chunks(3).enumerate() {
             let first = &slice[0];
             let second = &slice[1];
             let third = &slice[2];
             for c in first.chars() {
                 // Look for the character in the two others
                 if second.contains(c) && third.contains(c) {
-                    result += Puzzle::char_to_priority(c);
+                    let priority = Puzzle::char_to_priority(c);
+                    result.push(Found {
+                        holder: index + 1,
+                        item: c,
+                        priority,
+                    });
+                    observer.trace(|| item_event("Badge", "group", index, c, priority));
                     break;
                 }
             }
@@ -61,28 +142,163 @@ impl Puzzle {
     }
 }
 
-/// Test from puzzle input
+/// Event of an item found in a rucksack or a group, both numbered from 1
+fn item_event(name: &str, holder: &str, index: usize, item: char, priority: u32) -> json::Object {
+    event(name)
+        .value(holder, index + 1)
+        .string("item", &item.to_string())
+        .value("priority", priority)
+}
+
+impl Observe for Puzzle {
+    /// A `Shared` event for the item in both compartments of each rucksack in the first part, a
+    /// `Badge` event for the item of each group in the second one
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError> {
+        let puzzle = Self::from_input(input)?;
+        let found = match part {
+            1 => puzzle.shared(observer),
+            _ => puzzle.badges(observer),
+        };
+        Ok(found
+            .iter()
+            .map(|found| found.priority)
+            .sum::<u32>()
+            .to_string())
+    }
+}
+
+impl Explain for Puzzle {
+    /// The item shared by the compartments of each rucksack, or the badge of each group
+    fn explain(&self, part: u32) -> String {
+        let (holder, found) = match part {
+            1 => ("rucksack", self.shared(&mut ())),
+            _ => ("group", self.badges(&mut ())),
+        };
+        let mut text = String::new();
+        for found in found {
+            text.push_str(&format!(
+                "{holder} {}: `{}` (priority {})\n",
+                found.holder, found.item, found.priority
+            ));
+        }
+        text
+    }
+}
+
+impl Generate for Puzzle {
+    /// Rucksacks of `size` groups of three elves
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut letters = ('a'..='z').chain('A'..='Z').collect::<Vec<_>>();
+        let mut input = String::new();
+        for _ in 0..size {
+            // Every rucksack gets its own letters so that the badge is the only item of the group
+            // in all three rucksacks, and a single item is shared by the compartments of each one
+            rng.shuffle(&mut letters);
+            let (badge, rest) = letters.split_first().unwrap();
+            for items in rest.chunks(17) {
+                let (shared, items) = items.split_first().unwrap();
+                let (first_items, second_items) = items.split_at(8);
+
+                let length = rng.range(2..=16);
+                let mut compartments = [vec![*shared], vec![*shared]];
+                compartments[rng.below(2)].push(*badge);
+                for (compartment, items) in compartments.iter_mut().zip([first_items, second_items])
+                {
+                    while compartment.len() < length {
+                        compartment.push(*rng.pick(items));
+                    }
+                    rng.shuffle(compartment);
+                }
+                input.extend(compartments.concat());
+                input.push('\n');
+            }
+        }
+        input
+    }
+}
+
+impl Reference for Puzzle {
+    fn reference_part1(&self) -> u32 {
+        // Check every item type against both compartments
+        let mut result = 0;
+        for rucksack in &self.rucksacks {
+            let (first, second) = rucksack.split_at(rucksack.len() / 2);
+            for c in ('a'..='z').chain('A'..='Z') {
+                if first.contains(c) && second.contains(c) {
+                    result += Puzzle::char_to_priority(c);
+                }
+            }
+        }
+        result
+    }
+
+    fn reference_part2(&self) -> u32 {
+        let mut result = 0;
+        for group in self.rucksacks.chunks(3) {
+            for c in ('a'..='z').chain('A'..='Z') {
+                if group.iter().all(|rucksack| rucksack.contains(c)) {
+                    result += Puzzle::char_to_priority(c);
+                }
+            }
+        }
+        result
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s)
+    }
+}
+
+/// Test every example input against its manifest
+#[test]
+fn examples() {
+    aoc_core::testing::check_examples::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test inputs of the generator
+#[test]
+fn generated() {
+    aoc_core::testing::check_generated::<Puzzle>();
+}
+
+/// Compare with the reference solution on generated inputs
+#[test]
+fn reference() {
+    aoc_core::testing::check_reference::<Puzzle>();
+}
+
+/// Test mutated inputs never panic
+#[test]
+fn fuzz() {
+    aoc_core::testing::check_fuzz::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test malformed input
 #[test]
-fn test01() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test01.txt");
-    assert_eq!(puzzle.part1(), 157);
-    assert_eq!(puzzle.part2(), 70);
+fn malformed() {
+    let error = "vJrwpWtwJgWrhcsFMMfFFhFp\njq1\n"
+        .parse::<Puzzle>()
+        .err()
+        .unwrap();
+    assert_eq!((error.line, error.column), (2, 3));
 }
 
-/// Test from user input
+/// Test the item of each rucksack and of each group is found
 #[test]
-fn test02() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test02.txt");
-    assert_eq!(puzzle.part1(), 7831);
-    assert_eq!(puzzle.part2(), 2683);
-}
-
-fn main() {
-    let mut puzzle = Puzzle::new();
-    let input = std::env::args().nth(1).expect("No input file");
-    puzzle.configure(&input);
-    println!("{}", puzzle.part1());
-    println!("{}", puzzle.part2());
+fn explained() {
+    let puzzle = std::fs::read_to_string("test01.txt")
+        .unwrap()
+        .parse::<Puzzle>()
+        .unwrap();
+    let items = |found: Vec<Found>| found.iter().map(|found| found.item).collect::<String>();
+    assert_eq!(items(puzzle.shared(&mut ())), "pLPvts");
+    assert_eq!(items(puzzle.badges(&mut ())), "rZ");
+    assert_eq!(
+        puzzle.explain(2),
+        "group 1: `r` (priority 18)\ngroup 2: `Z` (priority 52)\n"
+    );
 }
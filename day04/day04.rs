@@ -2,57 +2,182 @@
 //! [Day 4: Camp Cleanup](https://adventofcode.com/2022/day/4)
 //!
 
-struct Puzzle {
+#![warn(missing_docs)]
+
+use aoc_core::observe::event;
+use aoc_core::parse::{self, Issues, Line, Scanner};
+use aoc_core::{Day, Generate, Lint, Observe, Observer, ParseError, Reference, Rng, Solution};
+use std::str::FromStr;
+
+/// Section assignments of each pair of elves
+pub struct Puzzle {
     assignment_pairs: Vec<(u32, u32, u32, u32)>,
 }
 
+/// The day as run by `aor`
+pub const DAY: Day = Day::new::<Puzzle>(4, "Camp Cleanup")
+    .with_observe::<Puzzle>()
+    .with_lint::<Puzzle>();
+
 impl Puzzle {
-    fn new() -> Self {
-        Self {
-            assignment_pairs: Vec::new(),
+    /// Whether an assignment of a pair, the first and last sections of each elf, fully contains
+    /// the other one
+    pub fn assignment_contained(a: &(u32, u32, u32, u32)) -> bool {
+        a.0 >= a.2 && a.1 <= a.3 || a.0 <= a.2 && a.1 >= a.3
+    }
+
+    /// Whether the assignments of a pair share at least a section
+    pub fn assignment_overlapped(a: &(u32, u32, u32, u32)) -> bool {
+        Puzzle::assignment_contained(a) || a.0 >= a.2 && a.0 <= a.3 || a.1 >= a.2 && a.1 <= a.3
+    }
+
+    /// Parse the assignments, going on after any pair in error
+    fn read(input: &str, issues: &mut Issues) -> Option<Self> {
+        let assignment_pairs = parse::lines(input)
+            .iter()
+            .filter_map(|line| issues.check(Self::parse_pair(line)))
+            .collect();
+        Some(Self { assignment_pairs })
+    }
+
+    /// Parse the assignments of a pair, like `2-4,6-8`
+    fn parse_pair(line: &Line) -> Result<(u32, u32, u32, u32), ParseError> {
+        let mut scanner = line.scan();
+        let (left_id_first, left_id_last) = Self::parse_range(&mut scanner)?;
+        scanner.tag(",")?;
+        let (right_id_first, right_id_last) = Self::parse_range(&mut scanner)?;
+        scanner.end()?;
+        Ok((left_id_first, left_id_last, right_id_first, right_id_last))
+    }
+
+    /// Parse the first and last sections assigned to an elf, like `2-4`
+    fn parse_range(scanner: &mut Scanner) -> Result<(u32, u32), ParseError> {
+        let start = scanner.clone();
+        let first = scanner.number()?;
+        scanner.tag("-")?;
+        let last = scanner.number()?;
+        if first > last {
+            return Err(ParseError {
+                found: format!("`{first}-{last}`"),
+                ..start.error("a range of sections in increasing order")
+            });
         }
+        Ok((first, last))
     }
+}
 
-    fn configure(&mut self, path: &str) {
-        let data = std::fs::read_to_string(path).unwrap();
-        let mut lines = data.split('\n').collect::<Vec<_>>();
-        lines.pop();
-
-        for assignment_pair in lines {
-            let (assignment_left, assignment_right) = assignment_pair.split_once(',').unwrap();
-            let (left_id_first, left_id_last) = assignment_left.split_once('-').unwrap();
-            let (right_id_first, right_id_last) = assignment_right.split_once('-').unwrap();
-            self.assignment_pairs.push((
-                left_id_first.parse().unwrap(),
-                left_id_last.parse().unwrap(),
-                right_id_first.parse().unwrap(),
-                right_id_last.parse().unwrap(),
-            ));
+impl Solution for Puzzle {
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut issues = Issues::default();
+        let puzzle = Self::read(input, &mut issues);
+        issues.first(puzzle)
+    }
+
+    fn part1(&self) -> u32 {
+        self.count(Puzzle::assignment_contained, &mut ())
+    }
+
+    fn part2(&self) -> u32 {
+        self.count(Puzzle::assignment_overlapped, &mut ())
+    }
+}
+
+impl Lint for Puzzle {
+    fn lint(input: &str) -> Vec<ParseError> {
+        let mut issues = Issues::default();
+        Self::read(input, &mut issues);
+        issues.errors()
+    }
+}
+
+impl Puzzle {
+    /// Assignments of each pair, the first and last sections of each elf
+    pub fn assignment_pairs(&self) -> &[(u32, u32, u32, u32)] {
+        &self.assignment_pairs
+    }
+
+    /// Count of the pairs whose assignments match a condition
+    pub fn count(
+        &self,
+        condition: fn(&(u32, u32, u32, u32)) -> bool,
+        observer: &mut dyn Observer,
+    ) -> u32 {
+        let mut result = 0;
+        for (index, assignment) in self.assignment_pairs.iter().enumerate() {
+            let counted = condition(assignment);
+            if counted {
+                result += 1;
+            }
+            observer.trace(|| {
+                event("Pair")
+                    .value("pair", index + 1)
+                    .value("counted", counted)
+            });
         }
+        result
     }
+}
 
-    fn assignment_contained(a: &(u32, u32, u32, u32)) -> bool {
-        a.0 >= a.2 && a.1 <= a.3 || a.0 <= a.2 && a.1 >= a.3
+impl Observe for Puzzle {
+    /// A `Pair` event telling whether each pair is counted, as fully contained in the first part
+    /// or as overlapping in the second one
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError> {
+        let condition = if part == 1 {
+            Puzzle::assignment_contained
+        } else {
+            Puzzle::assignment_overlapped
+        };
+        Ok(Self::from_input(input)?
+            .count(condition, observer)
+            .to_string())
     }
+}
 
-    fn assignment_overlapped(a: &(u32, u32, u32, u32)) -> bool {
-        Puzzle::assignment_contained(a) || a.0 >= a.2 && a.0 <= a.3 || a.1 >= a.2 && a.1 <= a.3
+impl Generate for Puzzle {
+    /// `size` pairs of section assignments, some of them contained in the other
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut input = String::new();
+        for _ in 0..size {
+            let left_first = rng.range(1..=99);
+            let left_last = rng.range(left_first..=99);
+            let (right_first, right_last) = if rng.chance(0.3) {
+                let first = rng.range(left_first..=left_last);
+                (first, rng.range(first..=left_last))
+            } else {
+                let first = rng.range(1..=99);
+                (first, rng.range(first..=99))
+            };
+            let pair = [(left_first, left_last), (right_first, right_last)];
+            let [(a, b), (c, d)] = if rng.chance(0.5) {
+                pair
+            } else {
+                [pair[1], pair[0]]
+            };
+            input.push_str(&format!("{a}-{b},{c}-{d}\n"));
+        }
+        input
     }
+}
 
-    fn part1(&self) -> u32 {
+impl Reference for Puzzle {
+    fn reference_part1(&self) -> u32 {
+        // Check sections one by one
         let mut result = 0;
-        for assignment in &self.assignment_pairs {
-            if Puzzle::assignment_contained(assignment) {
+        for &(a, b, c, d) in &self.assignment_pairs {
+            if (a..=b).all(|s| (c..=d).contains(&s)) || (c..=d).all(|s| (a..=b).contains(&s)) {
                 result += 1;
             }
         }
         result
     }
 
-    fn part2(&self) -> u32 {
+    fn reference_part2(&self) -> u32 {
         let mut result = 0;
-        for assignment in &self.assignment_pairs {
-            if Puzzle::assignment_overlapped(assignment) {
+        for &(a, b, c, d) in &self.assignment_pairs {
+            if (a..=b).any(|s| (c..=d).contains(&s)) {
                 result += 1;
             }
         }
@@ -60,28 +185,72 @@ impl Puzzle {
     }
 }
 
-/// Test from puzzle input
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s)
+    }
+}
+
+/// Test every example input against its manifest
+#[test]
+fn examples() {
+    aoc_core::testing::check_examples::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test inputs of the generator
 #[test]
-fn test01() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test01.txt");
-    assert_eq!(puzzle.part1(), 2);
-    assert_eq!(puzzle.part2(), 4);
+fn generated() {
+    aoc_core::testing::check_generated::<Puzzle>();
 }
 
-/// Test from user input
+/// Compare with the reference solution on generated inputs
 #[test]
-fn test02() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test02.txt");
-    assert_eq!(puzzle.part1(), 556);
-    assert_eq!(puzzle.part2(), 876);
-}
-
-fn main() {
-    let mut puzzle = Puzzle::new();
-    let input = std::env::args().nth(1).expect("No input file");
-    puzzle.configure(&input);
-    println!("{}", puzzle.part1());
-    println!("{}", puzzle.part2());
+fn reference() {
+    aoc_core::testing::check_reference::<Puzzle>();
+}
+
+/// Test mutated inputs never panic
+#[test]
+fn fuzz() {
+    aoc_core::testing::check_fuzz::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test pairs containing one another are counted among the overlapping ones
+#[test]
+fn properties() {
+    aoc_core::testing::check_property::<Puzzle>("contained-overlap", |puzzle| {
+        let (contained, overlapped) = (puzzle.part1(), puzzle.part2());
+        if contained > overlapped {
+            return Err(format!(
+                "{contained} contained but {overlapped} overlapping"
+            ));
+        }
+        Ok(())
+    });
+}
+
+/// Test malformed input
+#[test]
+fn malformed() {
+    let error = "2-4,6-8\n2-3;4-5\n".parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (2, 4));
+
+    let error = "2-4,8-6\n".parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (1, 5));
+    assert_eq!(error.found, "`8-6`");
+}
+
+/// Test every malformed pair and inverted range is reported
+#[test]
+fn linted() {
+    aoc_core::testing::check_lint::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+
+    let errors = Puzzle::lint("7-3,1-2\n2-4,6-8\n2-3;4-5\n5-5,9-1\n");
+    let positions = errors
+        .iter()
+        .map(|e| (e.line, e.column))
+        .collect::<Vec<_>>();
+    assert_eq!(positions, [(1, 1), (3, 4), (4, 5)]);
 }
@@ -2,55 +2,183 @@
 //! [Day 5: Supply Stacks](https://adventofcode.com/2022/day/5)
 //!
 
-struct Puzzle {
+#![warn(missing_docs)]
+
+use aoc_core::observe::event;
+use aoc_core::parse::{self, Issues, Line, Scanner};
+use aoc_core::{Day, Generate, Lint, Observe, Observer, ParseError, Reference, Rng, Solution};
+use std::str::FromStr;
+
+/// Stacks of crates and the moves of the crane
+pub struct Puzzle {
     crates_stacks: Vec<Vec<char>>,
     instructions: Vec<(usize, usize, usize)>,
 }
 
+/// The day as run by `aor`
+pub const DAY: Day = Day::new::<Puzzle>(5, "Supply Stacks")
+    .with_observe::<Puzzle>()
+    .with_lint::<Puzzle>();
+
 impl Puzzle {
-    fn new() -> Self {
-        Self {
-            crates_stacks: Vec::new(),
-            instructions: Vec::new(),
+    /// Parse the number of an existing stack
+    fn parse_stack(scanner: &mut Scanner, stack_count: usize) -> Result<usize, ParseError> {
+        let start = scanner.clone();
+        let stack = scanner.number()?;
+        if stack == 0 || stack > stack_count {
+            return Err(start.error(format!("a stack between 1 and {stack_count}")));
         }
+        Ok(stack)
     }
 
-    fn configure(&mut self, path: &str) {
-        let mut data = std::fs::read_to_string(path).unwrap();
-        data.pop();
-        let (crates, instructions) = data.split_once("\n\n").unwrap();
-        let mut crates = crates.split('\n').collect::<Vec<_>>();
+    /// Parse the drawing then the procedure, going on after any line of crates or move in error
+    fn read(input: &str, issues: &mut Issues) -> Option<Self> {
+        let blocks = issues.check(parse::blocks(input))?;
+        let (drawing, procedure) = match blocks.as_slice() {
+            [drawing, procedure] => (drawing, procedure),
+            [drawing] => {
+                let following = drawing.last().unwrap().following();
+                issues.push(following.scan().error("a blank line"));
+                return None;
+            }
+            [_, _, extra, ..] => {
+                issues.push(extra[0].error(1, "end of input"));
+                return None;
+            }
+            [] => {
+                issues.push(parse::end_of_input(input, "a drawing of the crates"));
+                return None;
+            }
+        };
+
+        // The last line of the drawing numbers the stacks, like " 1   2   3 "
+        let (numbers, crates) = drawing.split_last().unwrap();
+        let stack_count = issues.check(Self::parse_numbers(numbers))?;
+        let mut crates_stacks = vec![Vec::new(); stack_count];
 
-        // The character before the last on the last line is the count of stacks
-        let stack_count = crates.pop().unwrap().chars().nth_back(1).unwrap();
-        let stack_count = stack_count.to_digit(10).unwrap() as usize;
-        for _ in 0..stack_count {
-            self.crates_stacks.push(Vec::new());
+        // Fill the crates stacks from the bottom
+        for crate_line in crates.iter().rev() {
+            issues.check(Self::parse_crates(crate_line, &mut crates_stacks));
         }
 
-        // Fill the crates stacks
-        for crate_line in crates.iter().rev() {
-            for stack_index in 0..stack_count {
-                // Crate character is every 4 characters starting at the second
-                let c = crate_line.chars().nth(1 + 4 * stack_index).unwrap();
-                if c.is_ascii_alphabetic() {
-                    self.crates_stacks[stack_index].push(c);
-                }
+        // Follow the heights of the stacks to only move existing crates
+        let mut heights = crates_stacks.iter().map(Vec::len).collect::<Vec<_>>();
+        let mut instructions = Vec::new();
+        for instruction_line in procedure {
+            let instruction = Self::parse_instruction(instruction_line, &heights);
+            if let Some((count, from, to)) = issues.check(instruction) {
+                heights[from - 1] -= count;
+                heights[to - 1] += count;
+                instructions.push((count, from, to));
+            }
+        }
+
+        Some(Self {
+            crates_stacks,
+            instructions,
+        })
+    }
+
+    /// Parse the numbers of the stacks, like " 1   2   3 ", into their count
+    fn parse_numbers(numbers: &Line) -> Result<usize, ParseError> {
+        let mut stack_count = 0;
+        let mut scanner = numbers.scan();
+        loop {
+            scanner.tag(if stack_count == 0 { " " } else { "  " })?;
+            let start = scanner.clone();
+            if scanner.number::<usize>()? != stack_count + 1 {
+                return Err(start.error(format!("stack number `{}`", stack_count + 1)));
+            }
+            stack_count += 1;
+
+            // Trailing whitespaces might have been trimmed
+            if !scanner.eat(" ") || scanner.is_end() {
+                break;
+            }
+        }
+        scanner.end()?;
+        Ok(stack_count)
+    }
+
+    /// Push the crates of a level on their stacks, every crate like "[A]" or an empty slot
+    /// takes 3 characters
+    fn parse_crates(crate_line: &Line, crates_stacks: &mut [Vec<char>]) -> Result<(), ParseError> {
+        let mut scanner = crate_line.scan();
+        for (stack_index, stack) in crates_stacks.iter_mut().enumerate() {
+            if stack_index != 0 && !scanner.is_end() {
+                scanner.tag(" ")?;
+            }
+            if scanner.is_end() || scanner.eat("   ") {
+                continue;
             }
+            scanner.tag("[")?;
+            match scanner.peek() {
+                Some(c) if c.is_ascii_alphabetic() => stack.push(c),
+                _ => return Err(scanner.error("a crate letter")),
+            }
+            scanner.next_char();
+            scanner.tag("]")?;
         }
+        scanner.end()
+    }
 
-        // Split instructions by whitespaces to get count of crates to move, source and destination stacks
-        let instructions = instructions.split('\n').collect::<Vec<_>>();
-        for instruction_line in instructions {
-            let mut split = instruction_line.split_ascii_whitespace();
-            let count = split.nth(1).unwrap().parse::<usize>().unwrap();
-            let from = split.nth(1).unwrap().parse::<usize>().unwrap();
-            let to = split.nth(1).unwrap().parse::<usize>().unwrap();
-            self.instructions.push((count, from, to));
+    /// Parse a move of existing crates, like "move 1 from 2 to 1", given the heights of the
+    /// stacks
+    fn parse_instruction(
+        instruction_line: &Line,
+        heights: &[usize],
+    ) -> Result<(usize, usize, usize), ParseError> {
+        let mut scanner = instruction_line.scan();
+        scanner.tag("move ")?;
+        let count_start = scanner.clone();
+        let count = scanner.number()?;
+        scanner.tag(" from ")?;
+        let from = Self::parse_stack(&mut scanner, heights.len())?;
+        scanner.tag(" to ")?;
+        let to = Self::parse_stack(&mut scanner, heights.len())?;
+        scanner.end()?;
+        if count > heights[from - 1] {
+            return Err(
+                count_start.error(format!("a count of at most {} crates", heights[from - 1]))
+            );
         }
+        Ok((count, from, to))
+    }
+}
+
+impl Solution for Puzzle {
+    type Part1 = String;
+    type Part2 = String;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut issues = Issues::default();
+        let puzzle = Self::read(input, &mut issues);
+        issues.first(puzzle)
     }
 
     fn part1(&self) -> String {
+        self.crate_mover_9000(&mut ())
+    }
+
+    fn part2(&self) -> String {
+        self.crate_mover_9001(&mut ())
+    }
+}
+
+impl Puzzle {
+    /// Crates of each stack, from the bottom
+    pub fn crates_stacks(&self) -> &[Vec<char>] {
+        &self.crates_stacks
+    }
+
+    /// Moves of the crane: a count of crates, the stack they are taken from and the one they
+    /// are put on, stacks being numbered from 1
+    pub fn instructions(&self) -> &[(usize, usize, usize)] {
+        &self.instructions
+    }
+
+    /// Crates on top of the stacks once moved one at a time
+    pub fn crate_mover_9000(&self, observer: &mut dyn Observer) -> String {
         let mut result = String::new();
 
         let mut crates_stacks = self.crates_stacks.clone();
@@ -60,6 +188,15 @@ impl Puzzle {
                 let c = crates_stacks[from - 1].pop().unwrap();
                 crates_stacks[to - 1].push(c);
             }
+            observer.trace(|| {
+                event("Move")
+                    .value("count", count)
+                    .value("from", from)
+                    .value("to", to)
+            });
+            if observer.step() {
+                observer.frame(&draw(&crates_stacks));
+            }
         }
 
         for stack in &mut crates_stacks {
@@ -71,7 +208,8 @@ impl Puzzle {
         result
     }
 
-    fn part2(&self) -> String {
+    /// Crates on top of the stacks once moved several at once
+    pub fn crate_mover_9001(&self, observer: &mut dyn Observer) -> String {
         let mut result = String::new();
 
         let mut crates_stacks = self.crates_stacks.clone();
@@ -84,6 +222,15 @@ impl Puzzle {
                 .to_vec();
             crates_stacks[to - 1].append(&mut moved);
             crates_stacks[from - 1].truncate(stack_len - count);
+            observer.trace(|| {
+                event("Move")
+                    .value("count", count)
+                    .value("from", from)
+                    .value("to", to)
+            });
+            if observer.step() {
+                observer.frame(&draw(&crates_stacks));
+            }
         }
 
         for stack in &mut crates_stacks {
@@ -96,28 +243,224 @@ impl Puzzle {
     }
 }
 
-/// Test from puzzle input
+impl Lint for Puzzle {
+    fn lint(input: &str) -> Vec<ParseError> {
+        let mut issues = Issues::default();
+        Self::read(input, &mut issues);
+        issues.errors()
+    }
+}
+
+impl Observe for Puzzle {
+    /// A `Move` event and a frame of the stacks after each move
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError> {
+        let puzzle = Self::from_input(input)?;
+        Ok(match part {
+            1 => puzzle.crate_mover_9000(observer),
+            _ => puzzle.crate_mover_9001(observer),
+        })
+    }
+}
+
+/// Drawing of the stacks like the one of the input
+pub fn draw(crates_stacks: &[Vec<char>]) -> String {
+    let height = crates_stacks.iter().map(Vec::len).max().unwrap_or(0);
+    let mut drawing = String::new();
+    for level in (0..height).rev() {
+        let row = crates_stacks
+            .iter()
+            .map(|stack| match stack.get(level) {
+                Some(c) => format!("[{c}]"),
+                None => String::from("   "),
+            })
+            .collect::<Vec<_>>();
+        drawing.push_str(row.join(" ").trim_end());
+        drawing.push('\n');
+    }
+    let numbers = (1..=crates_stacks.len())
+        .map(|number| format!(" {number} "))
+        .collect::<Vec<_>>();
+    drawing.push_str(&numbers.join(" "));
+    drawing.push('\n');
+    drawing
+}
+
+impl Generate for Puzzle {
+    /// Drawing of up to 9 stacks followed by `size` moves, each one moving existing crates
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let stack_count = rng.range(2..=9);
+        let mut stacks = (0..stack_count)
+            .map(|_| {
+                let height = rng.range(0..=8);
+                (0..height)
+                    .map(|_| rng.letter(26).to_ascii_uppercase())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        if stacks.iter().all(Vec::is_empty) {
+            stacks[0].push('A');
+        }
+
+        let mut input = String::new();
+        let height = stacks.iter().map(Vec::len).max().unwrap();
+        for level in (0..height).rev() {
+            let crates = stacks
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(c) => format!("[{c}]"),
+                    None => String::from("   "),
+                })
+                .collect::<Vec<_>>();
+            input.push_str(&crates.join(" "));
+            input.push('\n');
+        }
+        let numbers = (1..=stack_count)
+            .map(|number| format!(" {number} "))
+            .collect::<Vec<_>>();
+        input.push_str(&numbers.join(" "));
+        input.push_str("\n\n");
+
+        for _ in 0..size {
+            let filled = (0..stack_count)
+                .filter(|&stack| !stacks[stack].is_empty())
+                .collect::<Vec<_>>();
+            let from = *rng.pick(&filled);
+            let to = (from + rng.range(1..=stack_count - 1)) % stack_count;
+            let height = stacks[from].len();
+            let count = rng.range(1..=height);
+            let moved = stacks[from].split_off(height - count);
+            stacks[to].extend(moved);
+            input.push_str(&format!("move {count} from {} to {}\n", from + 1, to + 1));
+        }
+        input
+    }
+}
+
+impl Puzzle {
+    /// Rearrange stacks written from their top crate, moving crates one at a time or all at once
+    fn rearrange(&self, one_at_a_time: bool) -> String {
+        let mut stacks = self
+            .crates_stacks
+            .iter()
+            .map(|stack| stack.iter().rev().collect::<String>())
+            .collect::<Vec<_>>();
+
+        for &(count, from, to) in &self.instructions {
+            let moved = stacks[from - 1].drain(..count).collect::<String>();
+            let moved = if one_at_a_time {
+                moved.chars().rev().collect()
+            } else {
+                moved
+            };
+            stacks[to - 1].insert_str(0, &moved);
+        }
+
+        stacks
+            .iter()
+            .filter_map(|stack| stack.chars().next())
+            .collect()
+    }
+}
+
+impl Reference for Puzzle {
+    fn reference_part1(&self) -> String {
+        self.rearrange(true)
+    }
+
+    fn reference_part2(&self) -> String {
+        self.rearrange(false)
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s)
+    }
+}
+
+/// Test every example input against its manifest
+#[test]
+fn examples() {
+    aoc_core::testing::check_examples::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test inputs of the generator
+#[test]
+fn generated() {
+    aoc_core::testing::check_generated::<Puzzle>();
+}
+
+/// Compare with the reference solution on generated inputs
+#[test]
+fn reference() {
+    aoc_core::testing::check_reference::<Puzzle>();
+}
+
+/// Test mutated inputs never panic
 #[test]
-fn test01() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test01.txt");
+fn fuzz() {
+    aoc_core::testing::check_fuzz::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test puzzle input with Windows line endings and without final newline
+#[test]
+fn crlf() {
+    let input = std::fs::read_to_string("test01.txt").unwrap();
+    let puzzle = input
+        .trim_end()
+        .replace('\n', "\r\n")
+        .parse::<Puzzle>()
+        .unwrap();
     assert_eq!(puzzle.part1(), "CMZ");
     assert_eq!(puzzle.part2(), "MCD");
 }
 
-/// Test from user input
+/// Test malformed input
+#[test]
+fn malformed() {
+    let error = "    [D]\n[N] [C]\n 1   2 \n\nmove 1 from 3 to 1\n"
+        .parse::<Puzzle>()
+        .err()
+        .unwrap();
+    assert_eq!((error.line, error.column), (5, 13));
+
+    let error = "    [D]\n[N] [C]\n 1   2 \n\nmove 2 from 2 to 1\nmove 4 from 1 to 2\n"
+        .parse::<Puzzle>()
+        .err()
+        .unwrap();
+    assert_eq!((error.line, error.column), (6, 6));
+}
+
+/// Test every malformed line of crates and move is reported
+#[test]
+fn linted() {
+    aoc_core::testing::check_lint::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+
+    let errors = Puzzle::lint(
+        "    [1]\n[N] [C]\n 1   2 \n\nmove 1 from 3 to 1\nmove 2 from 2 to 1\nmove 1 from 1 to 2\n",
+    );
+    let positions = errors
+        .iter()
+        .map(|e| (e.line, e.column))
+        .collect::<Vec<_>>();
+    assert_eq!(positions, [(1, 6), (5, 13), (6, 6)]);
+}
+
+/// Test every move is drawn and traced
 #[test]
-fn test02() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test02.txt");
-    assert_eq!(puzzle.part1(), "RFFFWBPNS");
-    assert_eq!(puzzle.part2(), "CQQBBJFCS");
-}
-
-fn main() {
-    let mut puzzle = Puzzle::new();
-    let input = std::env::args().nth(1).expect("No input file");
-    puzzle.configure(&input);
-    println!("{}", puzzle.part1());
-    println!("{}", puzzle.part2());
+fn animated() {
+    let input = std::fs::read_to_string("test01.txt").unwrap();
+    let mut recorder = aoc_core::observe::Recorder::default();
+    assert_eq!(Puzzle::observe(&input, 1, &mut recorder).unwrap(), "CMZ");
+    assert_eq!(recorder.frames.len(), 4);
+    assert_eq!(
+        recorder.frames[0],
+        "[D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3 \n"
+    );
+    assert_eq!(
+        recorder.events[0],
+        r#"{"event":"Move","count":1,"from":2,"to":1}"#
+    );
 }
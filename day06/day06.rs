@@ -2,104 +2,268 @@
 //! [Day 6: Tuning Trouble](https://adventofcode.com/2022/day/6)
 //!
 
+#![warn(missing_docs)]
+
+use aoc_core::observe::event;
+use aoc_core::params::{self, Value};
+use aoc_core::{
+    parse, Configure, Day, Generate, Observe, Observer, ParseError, Reference, Rng, Solution,
+};
 use std::collections::HashSet;
+use std::str::FromStr;
 
-struct Puzzle {
-    signal: Vec<char>,
+/// Constants of the puzzle
+#[derive(Clone, Debug)]
+pub struct Params {
+    /// Length of the start-of-packet marker of the first part
+    pub packet_marker: usize,
+    /// Length of the start-of-message marker of the second part
+    pub message_marker: usize,
 }
 
-impl Puzzle {
-    fn new() -> Self {
-        Self { signal: Vec::new() }
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            packet_marker: 4,
+            message_marker: 14,
+        }
+    }
+}
+
+impl params::Params for Params {
+    fn fields(&mut self) -> Vec<(&'static str, &mut dyn Value)> {
+        vec![
+            ("packet_marker", &mut self.packet_marker),
+            ("message_marker", &mut self.message_marker),
+        ]
     }
 
-    fn configure(&mut self, path: &str) {
-        let mut data = std::fs::read_to_string(path).unwrap();
-        data.pop();
-        self.signal = data.chars().collect();
+    fn check(&self) -> Result<(), String> {
+        if self.packet_marker == 0 || self.message_marker == 0 {
+            return Err(String::from("markers are at least one character long"));
+        }
+        Ok(())
     }
+}
 
-    fn find_marker(&self, length: usize) -> usize {
-        let mut result = length;
-        for slice in self.signal.windows(length) {
+/// Datastream received by the device
+pub struct Puzzle {
+    signal: Vec<char>,
+    params: Params,
+}
+
+/// The day as run by `aor`
+pub const DAY: Day = Day::new::<Puzzle>(6, "Tuning Trouble")
+    .with_observe::<Puzzle>()
+    .with_params::<Puzzle>();
+
+impl Puzzle {
+    /// Count of characters received once the last `length` ones are all different, if they ever
+    /// are, `length` cannot be zero
+    pub fn find_marker(&self, length: usize, observer: &mut dyn Observer) -> Option<usize> {
+        for (start, slice) in self.signal.windows(length).enumerate() {
             // Transform the slice into a set to merge duplicated characters. If the size
             // stays the same, there is not duplicate entry
             let characters = slice.iter().copied().collect::<HashSet<_>>();
+            let end = start + length;
+            observer.trace(|| {
+                event("Window")
+                    .value("end", end)
+                    .value("distinct", characters.len())
+            });
             if characters.len() == length {
-                break;
+                return Some(end);
             }
-            result += 1;
         }
-        result
+        None
+    }
+}
+
+impl Solution for Puzzle {
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Self::parse_with(input, Params::default())
     }
 
     fn part1(&self) -> usize {
-        self.find_marker(4)
+        self.find_marker(self.params.packet_marker, &mut ())
+            .unwrap()
     }
 
     fn part2(&self) -> usize {
-        self.find_marker(14)
+        self.find_marker(self.params.message_marker, &mut ())
+            .unwrap()
+    }
+}
+
+impl Configure for Puzzle {
+    type Params = Params;
+
+    fn parse_with(input: &str, params: Params) -> Result<Self, ParseError> {
+        let lines = parse::lines(input);
+        let line = match lines.as_slice() {
+            [line] => line,
+            [] => return Err(parse::end_of_input(input, "a datastream")),
+            [_, extra, ..] => return Err(extra.error(1, "end of input")),
+        };
+
+        let mut scanner = line.scan();
+        while let Some(c) = scanner.peek() {
+            if !c.is_ascii_lowercase() {
+                return Err(scanner.error("a lowercase letter"));
+            }
+            scanner.next_char();
+        }
+
+        // Both markers have to be received for the answers to exist
+        let puzzle = Self {
+            signal: line.text.chars().collect(),
+            params,
+        };
+        let markers = [
+            (puzzle.params.packet_marker, "a start-of-packet marker"),
+            (puzzle.params.message_marker, "a start-of-message marker"),
+        ];
+        for (length, expected) in markers {
+            if puzzle.find_marker(length, &mut ()).is_none() {
+                return Err(scanner.error(expected));
+            }
+        }
+        Ok(puzzle)
     }
 }
 
-/// Test from puzzle input
+impl Observe for Puzzle {
+    /// A `Window` event with the count of distinct characters of each window tried, up to the
+    /// marker
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError> {
+        let puzzle = Self::from_input(input)?;
+        let length = match part {
+            1 => puzzle.params.packet_marker,
+            _ => puzzle.params.message_marker,
+        };
+        Ok(puzzle.find_marker(length, observer).unwrap().to_string())
+    }
+}
+
+impl Generate for Puzzle {
+    /// Datastream of `size` characters, at least long enough to hold a start-of-message marker
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let length = size.max(14);
+        let mut signal = (0..length).map(|_| rng.letter(10)).collect::<Vec<_>>();
+
+        // Out of 10 letters there is always a duplicate among 14, so the marker is placed on purpose
+        let mut marker = ('a'..='z').collect::<Vec<_>>();
+        rng.shuffle(&mut marker);
+        let start = rng.range(0..=length - 14);
+        signal[start..start + 14].copy_from_slice(&marker[..14]);
+
+        let mut input = signal.into_iter().collect::<String>();
+        input.push('\n');
+        input
+    }
+}
+
+impl Puzzle {
+    /// Position after the first window of distinct characters if any, comparing every pair of
+    /// them
+    fn find_marker_pairwise(&self, length: usize) -> Option<usize> {
+        (length..=self.signal.len()).find(|&end| {
+            let window = &self.signal[end - length..end];
+            (0..length).all(|i| (i + 1..length).all(|j| window[i] != window[j]))
+        })
+    }
+}
+
+impl Reference for Puzzle {
+    fn reference_part1(&self) -> usize {
+        self.find_marker_pairwise(self.params.packet_marker)
+            .unwrap()
+    }
+
+    fn reference_part2(&self) -> usize {
+        self.find_marker_pairwise(self.params.message_marker)
+            .unwrap()
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s)
+    }
+}
+
+/// Test every example input against its manifest
 #[test]
-fn test01() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test01.txt");
-    assert_eq!(puzzle.part1(), 7);
-    assert_eq!(puzzle.part2(), 19);
+fn examples() {
+    aoc_core::testing::check_examples::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
 }
 
-/// Test from puzzle input
+/// Test inputs of the generator
 #[test]
-fn test02() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test02.txt");
-    assert_eq!(puzzle.part1(), 5);
-    assert_eq!(puzzle.part2(), 23);
+fn generated() {
+    aoc_core::testing::check_generated::<Puzzle>();
 }
 
-/// Test from puzzle input
+/// Compare with the reference solution on generated inputs
 #[test]
-fn test03() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test03.txt");
-    assert_eq!(puzzle.part1(), 6);
-    assert_eq!(puzzle.part2(), 23);
+fn reference() {
+    aoc_core::testing::check_reference::<Puzzle>();
 }
 
-/// Test from puzzle input
+/// Test mutated inputs never panic
 #[test]
-fn test04() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test04.txt");
-    assert_eq!(puzzle.part1(), 10);
-    assert_eq!(puzzle.part2(), 29);
+fn fuzz() {
+    aoc_core::testing::check_fuzz::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
 }
 
-/// Test from puzzle input
+/// Test markers end after the first window of distinct characters
 #[test]
-fn test05() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test05.txt");
-    assert_eq!(puzzle.part1(), 11);
-    assert_eq!(puzzle.part2(), 26);
+fn properties() {
+    aoc_core::testing::check_property::<Puzzle>("marker-window", |puzzle| {
+        let distinct = |window: &[char]| {
+            (0..window.len()).all(|i| (i + 1..window.len()).all(|j| window[i] != window[j]))
+        };
+        for length in 1..=14 {
+            let marker = puzzle.find_marker(length, &mut ());
+            let first = puzzle.signal.windows(length).position(distinct);
+            if marker != first.map(|start| start + length) {
+                return Err(format!(
+                    "marker of length {length} at {marker:?}, first distinct window at {first:?}"
+                ));
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Test malformed input
+#[test]
+fn malformed() {
+    let error = "mjqjpqmgbljsphdztnvjfqwrcgsmlb\nabc\n"
+        .parse::<Puzzle>()
+        .err()
+        .unwrap();
+    assert_eq!((error.line, error.column), (2, 1));
+
+    // Without marker there is no answer
+    for (input, column) in [("abc\n", 4), ("aaaa\n", 5), ("abcdefghijklm\n", 14)] {
+        let error = input.parse::<Puzzle>().err().unwrap();
+        assert_eq!((error.line, error.column), (1, column));
+    }
 }
 
-/// Test from user input
+/// Test the lengths of the markers can be changed
 #[test]
-fn test06() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test06.txt");
-    assert_eq!(puzzle.part1(), 1356);
-    assert_eq!(puzzle.part2(), 2564);
-}
-
-fn main() {
-    let mut puzzle = Puzzle::new();
-    let input = std::env::args().nth(1).expect("No input file");
-    puzzle.configure(&input);
-    println!("{}", puzzle.part1());
-    println!("{}", puzzle.part2());
+fn params() {
+    let params = Params {
+        packet_marker: 2,
+        message_marker: 5,
+    };
+    let puzzle = Puzzle::from_input_with("aabcdeb\n", params).unwrap();
+    assert_eq!((puzzle.part1(), puzzle.part2()), (3, 6));
 }
@@ -2,109 +2,574 @@
 //! [Day 7: No Space Left On Device](https://adventofcode.com/2022/day/7)
 //!
 
+#![warn(missing_docs)]
+
+use aoc_core::observe::event;
+use aoc_core::params::{self, Value};
+use aoc_core::parse::{self, Issues, Line};
+use aoc_core::{
+    Configure, Day, Explain, Generate, Lint, Observe, Observer, ParseError, Reference, Rng,
+    Solution,
+};
 use std::collections::HashMap;
+use std::str::FromStr;
 
-const FILESYSTEM_SIZE: usize = 70_000_000;
-const UPDATE_SIZE: usize = 30_000_000;
+/// Total space of the filesystem, in bytes
+pub const FILESYSTEM_SIZE: usize = 70_000_000;
+/// Free space the update needs
+pub const UPDATE_SIZE: usize = 30_000_000;
+/// Largest size of the directories added up in the first part
+pub const SMALL_DIRECTORY_SIZE: usize = 100_000;
 
-struct Puzzle {
-    directories_size: Vec<usize>,
+/// Constants of the puzzle
+#[derive(Clone, Debug)]
+pub struct Params {
+    /// Total space of the filesystem, in bytes
+    pub filesystem_size: usize,
+    /// Free space the update needs, less than the total space
+    pub update_size: usize,
+    /// Largest size of the directories added up in the first part
+    pub small_directory_size: usize,
 }
 
-impl Puzzle {
-    fn new() -> Self {
+impl Default for Params {
+    fn default() -> Self {
         Self {
-            directories_size: Vec::new(),
+            filesystem_size: FILESYSTEM_SIZE,
+            update_size: UPDATE_SIZE,
+            small_directory_size: SMALL_DIRECTORY_SIZE,
         }
     }
+}
 
-    fn configure(&mut self, path: &str) {
-        let mut data = std::fs::read_to_string(path).unwrap();
-        data.pop();
+impl params::Params for Params {
+    fn fields(&mut self) -> Vec<(&'static str, &mut dyn Value)> {
+        vec![
+            ("filesystem_size", &mut self.filesystem_size),
+            ("update_size", &mut self.update_size),
+            ("small_directory_size", &mut self.small_directory_size),
+        ]
+    }
+
+    fn check(&self) -> Result<(), String> {
+        // Deleting the root always frees enough space
+        if self.update_size >= self.filesystem_size {
+            return Err(String::from(
+                "the update needs less space than the filesystem has",
+            ));
+        }
+        Ok(())
+    }
+}
 
+/// Size of every directory of the filesystem explored by the terminal
+pub struct Puzzle {
+    /// Path of every directory, like `/a/e/`, in order
+    paths: Vec<String>,
+    directories_size: Vec<usize>,
+    params: Params,
+}
+
+/// Directory to delete to free enough space for the update, as chosen by [`Puzzle::deletion`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Deletion {
+    /// Path of the directory, like `/a/e/`
+    pub path: String,
+    /// Size of the directory, subdirectories included
+    pub size: usize,
+    /// Space the update lacks, the directory is larger
+    pub required: usize,
+}
+
+/// The day as run by `aor`
+pub const DAY: Day = Day::new::<Puzzle>(7, "No Space Left On Device")
+    .with_observe::<Puzzle>()
+    .with_params::<Puzzle>()
+    .with_lint::<Puzzle>()
+    .with_explain::<Puzzle>();
+
+impl Puzzle {
+    /// Follow the transcript of a normalized input through the filesystem, then size every
+    /// directory, see [`aoc_core::input::normalize`]
+    pub fn explore(
+        input: &str,
+        params: Params,
+        observer: &mut dyn Observer,
+    ) -> Result<Self, ParseError> {
+        let mut issues = Issues::default();
+        let puzzle = Self::read(input, params, observer, &mut issues);
+        issues.first(puzzle)
+    }
+
+    /// Follow the transcript like [`Puzzle::explore`], going on after any line in error
+    fn read(
+        input: &str,
+        params: Params,
+        observer: &mut dyn Observer,
+        issues: &mut Issues,
+    ) -> Option<Self> {
         let mut directories = HashMap::from([(String::from("/"), 0)]);
 
         // Get the size of directories (excluding subdirectories)
-        let mut current_dir = Vec::new();
-        for line in data.split('\n') {
-            if line == "$ cd /" {
-                current_dir = vec![String::from("/")];
-            } else if line == "$ cd .." {
-                current_dir.pop();
-                current_dir.pop();
-            } else if let Some((_, dir)) = line.split_once("$ cd ") {
-                current_dir.push(dir.to_string());
-                current_dir.push(String::from("/"));
-            } else if line == "$ ls" {
-                // Do nothing special, next entries will be parsed according to the current directory
-            } else if let Some((_, name)) = line.split_once("dir ") {
-                let mut dirname = current_dir.concat();
-                dirname.push_str(name);
-                dirname.push('/');
-                if directories.get(&dirname) == None {
-                    directories.insert(dirname, 0);
-                }
-            } else {
-                // This is a file entry description
-                let (size, _) = line.split_once(' ').unwrap();
-                let fullpath = current_dir.concat();
-                let dir_size = directories.get_mut(&fullpath).unwrap();
-                *dir_size += size.parse::<usize>().unwrap();
-            }
+        let mut current_dir = vec![String::from("/")];
+
+        // Files have to fit in the filesystem, which also keeps directory sizes in range
+        let mut used_space = 0usize;
+        for line in parse::lines(input) {
+            issues.check(Self::follow(
+                &line,
+                &params,
+                &mut directories,
+                &mut current_dir,
+                &mut used_space,
+                observer,
+            ));
         }
 
-        // Compute the size of directories (including subdirectories)
-        for path in directories.keys() {
+        // Compute the size of directories (including subdirectories), in the order of their paths
+        let mut paths = directories.keys().collect::<Vec<_>>();
+        paths.sort_unstable();
+        let mut directories_size = Vec::new();
+        for &path in &paths {
             let dir_size = directories
                 .iter()
                 .filter_map(|(k, v)| if k.starts_with(path) { Some(*v) } else { None })
                 .sum();
-            self.directories_size.push(dir_size);
+            observer.trace(|| event("Size").string("path", path).value("size", dir_size));
+            directories_size.push(dir_size);
         }
+
+        Some(Self {
+            paths: paths.into_iter().cloned().collect(),
+            directories_size,
+            params,
+        })
     }
 
-    fn part1(&self) -> usize {
-        self.directories_size
+    /// Follow a line of the transcript, a command or an entry listed in the current directory
+    fn follow(
+        line: &Line,
+        params: &Params,
+        directories: &mut HashMap<String, usize>,
+        current_dir: &mut Vec<String>,
+        used_space: &mut usize,
+        observer: &mut dyn Observer,
+    ) -> Result<(), ParseError> {
+        let mut scanner = line.scan();
+        if scanner.eat("$ ") {
+            if scanner.eat("ls") {
+                // Do nothing special, next entries will be parsed according to the current directory
+                scanner.end()?;
+            } else if scanner.eat("cd ") {
+                let start = scanner.clone();
+                match scanner.take_rest() {
+                    "/" => *current_dir = vec![String::from("/")],
+                    ".." => {
+                        if current_dir.len() == 1 {
+                            return Err(ParseError {
+                                found: String::from("`..`"),
+                                ..start.error("a subdirectory of the root")
+                            });
+                        }
+                        current_dir.pop();
+                        current_dir.pop();
+                    }
+                    "" => return Err(start.error("a directory name")),
+                    dir => {
+                        let mut dirname = current_dir.concat();
+                        dirname.push_str(dir);
+                        dirname.push('/');
+                        // An unlisted directory is entered all the same, so that the lines
+                        // following it are checked from there
+                        let listed = directories.contains_key(&dirname);
+                        directories.entry(dirname).or_insert(0);
+                        current_dir.push(dir.to_string());
+                        current_dir.push(String::from("/"));
+                        if !listed {
+                            return Err(start.error("a directory listed in the current one"));
+                        }
+                    }
+                }
+                observer.trace(|| event("Cd").string("path", &current_dir.concat()));
+            } else {
+                return Err(scanner.error("`cd` or `ls`"));
+            }
+        } else if scanner.eat("dir ") {
+            let name = scanner.take_rest();
+            if name.is_empty() {
+                return Err(scanner.error("a directory name"));
+            }
+            let mut dirname = current_dir.concat();
+            dirname.push_str(name);
+            dirname.push('/');
+            directories.entry(dirname).or_insert(0);
+        } else {
+            // This is a file entry description
+            if !scanner.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return Err(scanner.error("a command, a directory or a file"));
+            }
+            let start = scanner.clone();
+            let size = scanner.number::<usize>()?;
+            *used_space = used_space
+                .checked_add(size)
+                .filter(|&used| used <= params.filesystem_size)
+                .ok_or_else(|| {
+                    start.error(format!(
+                        "a file fitting in the {} filesystem",
+                        params.filesystem_size
+                    ))
+                })?;
+            scanner.tag(" ")?;
+            if scanner.take_rest().is_empty() {
+                return Err(scanner.error("a file name"));
+            }
+            let fullpath = current_dir.concat();
+            let dir_size = directories.get_mut(&fullpath).unwrap();
+            *dir_size += size;
+        }
+        Ok(())
+    }
+
+    /// Size of each directory, subdirectories included, in the order of their paths
+    pub fn directories_size(&self) -> &[usize] {
+        &self.directories_size
+    }
+
+    /// Path and size of the directories added up in the first part, in the order of their paths
+    pub fn small_directories(&self) -> Vec<(&str, usize)> {
+        let small = self.params.small_directory_size;
+        self.paths
             .iter()
-            .fold(0, |a, i| if *i <= 100_000 { a + i } else { a })
+            .zip(&self.directories_size)
+            .filter(|(_, &size)| size <= small)
+            .map(|(path, &size)| (path.as_str(), size))
+            .collect()
     }
 
-    fn part2(&self) -> usize {
+    /// Smallest directory freeing enough space for the update, the first one by path among
+    /// directories of the same size. There is none when no file takes up space
+    pub fn deletion(&self) -> Option<Deletion> {
+        let Params {
+            filesystem_size,
+            update_size,
+            ..
+        } = self.params;
         let used_space = self.directories_size.iter().max().unwrap();
-        let required_space = UPDATE_SIZE - (FILESYSTEM_SIZE - used_space);
+        let required = update_size.saturating_sub(filesystem_size - used_space);
 
-        self.directories_size.iter().fold(FILESYSTEM_SIZE, |a, i| {
-            if *i > required_space && *i < a {
-                *i
-            } else {
-                a
-            }
+        let (path, &size) = self
+            .paths
+            .iter()
+            .zip(&self.directories_size)
+            .filter(|(_, &size)| size > required)
+            .min_by_key(|(_, &size)| size)?;
+        Some(Deletion {
+            path: path.clone(),
+            size,
+            required,
         })
     }
 }
 
-/// Test from puzzle input
+impl Solution for Puzzle {
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Self::parse_with(input, Params::default())
+    }
+
+    fn part1(&self) -> usize {
+        self.small_directories().iter().map(|(_, size)| size).sum()
+    }
+
+    fn part2(&self) -> usize {
+        self.deletion()
+            .map_or(self.params.filesystem_size, |deletion| deletion.size)
+    }
+}
+
+impl Configure for Puzzle {
+    type Params = Params;
+
+    fn parse_with(input: &str, params: Params) -> Result<Self, ParseError> {
+        Self::explore(input, params, &mut ())
+    }
+}
+
+impl Lint for Puzzle {
+    fn lint(input: &str) -> Vec<ParseError> {
+        let mut issues = Issues::default();
+        Self::read(input, Params::default(), &mut (), &mut issues);
+        issues.errors()
+    }
+}
+
+impl Explain for Puzzle {
+    /// The small directories added up in the first part, the directory to delete in the second one
+    fn explain(&self, part: u32) -> String {
+        if part == 1 {
+            let mut text = String::new();
+            for (path, size) in self.small_directories() {
+                text.push_str(&format!("{path}: {size}\n"));
+            }
+            return text;
+        }
+        match self.deletion() {
+            Some(deletion) => format!(
+                "delete {} of size {}, {} is needed\n",
+                deletion.path, deletion.size, deletion.required
+            ),
+            None => String::from("no directory takes up space\n"),
+        }
+    }
+}
+
+impl Observe for Puzzle {
+    /// A `Cd` event with the path of the directory entered by each `cd` of the transcript, then a
+    /// `Size` event for every directory. Both parts find their answer among these sizes
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError> {
+        let input = aoc_core::input::normalize(input);
+        let puzzle = Self::explore(&input, Params::default(), observer)?;
+        Ok(match part {
+            1 => puzzle.part1(),
+            _ => puzzle.part2(),
+        }
+        .to_string())
+    }
+}
+
+/// Directories of a generated filesystem, indexed from the root
+struct Tree {
+    names: Vec<String>,
+    children: Vec<Vec<usize>>,
+    files: Vec<Vec<(usize, String)>>,
+}
+
+impl Tree {
+    /// Transcript listing a directory, then entering each of its subdirectories in turn
+    fn write(&self, dir: usize, rng: &mut Rng, input: &mut String) {
+        let mut entries = self.children[dir]
+            .iter()
+            .map(|&child| format!("dir {}", self.names[child]))
+            .chain(
+                self.files[dir]
+                    .iter()
+                    .map(|(size, name)| format!("{size} {name}")),
+            )
+            .collect::<Vec<_>>();
+        rng.shuffle(&mut entries);
+        input.push_str("$ ls\n");
+        for entry in entries {
+            input.push_str(&entry);
+            input.push('\n');
+        }
+
+        for &child in &self.children[dir] {
+            input.push_str(&format!("$ cd {}\n", self.names[child]));
+            self.write(child, rng, input);
+            input.push_str("$ cd ..\n");
+        }
+    }
+}
+
+fn generate_name(rng: &mut Rng) -> String {
+    let length = rng.range(1..=8);
+    (0..length).map(|_| rng.letter(26)).collect()
+}
+
+impl Generate for Puzzle {
+    /// Transcript exploring `size` directories, using between 40M and 70M so that an update
+    /// needs to free some space
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut tree = Tree {
+            names: vec![String::from("/")],
+            children: vec![Vec::new(); size],
+            files: vec![Vec::new(); size],
+        };
+        for dir in 1..size {
+            let parent = rng.below(dir);
+            let name = loop {
+                let name = generate_name(rng);
+                if tree.children[parent].iter().all(|&d| tree.names[d] != name) {
+                    break name;
+                }
+            };
+            tree.names.push(name);
+            tree.children[parent].push(dir);
+        }
+
+        // Small files are spread everywhere and a few large ones take the remaining space
+        let used = rng.range(FILESYSTEM_SIZE - UPDATE_SIZE + 1..=FILESYSTEM_SIZE - 1);
+        let largest = (used / (4 * size)).clamp(1, 300_000);
+        let mut total = 0;
+        for files in &mut tree.files {
+            for _ in 0..rng.range(0..=3) {
+                let file_size = rng.range(1..=largest);
+                let mut name = generate_name(rng);
+                if rng.chance(0.5) {
+                    name.push('.');
+                    name.push_str(&generate_name(rng));
+                }
+                files.push((file_size, name));
+                total += file_size;
+            }
+        }
+        let mut remaining = used - total;
+        for large in (1..=rng.range(1..=8)).rev() {
+            let file_size = if large == 1 {
+                remaining
+            } else {
+                rng.range(1..=remaining / 2)
+            };
+            remaining -= file_size;
+            let dir = rng.below(size);
+            tree.files[dir].push((file_size, generate_name(rng)));
+        }
+
+        let mut input = String::from("$ cd /\n");
+        tree.write(0, rng, &mut input);
+        input
+    }
+}
+
+impl Reference for Puzzle {
+    fn reference_part1(&self) -> usize {
+        self.directories_size
+            .iter()
+            .filter(|&&size| size <= self.params.small_directory_size)
+            .sum()
+    }
+
+    fn reference_part2(&self) -> usize {
+        // The root is the largest directory, the smallest one freeing enough space comes first
+        let mut sizes = self.directories_size.clone();
+        sizes.sort_unstable();
+        let unused = self.params.filesystem_size - sizes.last().unwrap();
+        *sizes
+            .iter()
+            .find(|&&size| unused + size >= self.params.update_size)
+            .unwrap()
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s)
+    }
+}
+
+/// Test every example input against its manifest
+#[test]
+fn examples() {
+    aoc_core::testing::check_examples::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test inputs of the generator
+#[test]
+fn generated() {
+    aoc_core::testing::check_generated::<Puzzle>();
+}
+
+/// Compare with the reference solution on generated inputs
+#[test]
+fn reference() {
+    aoc_core::testing::check_reference::<Puzzle>();
+}
+
+/// Test mutated inputs never panic
 #[test]
-fn test01() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test01.txt");
-    assert_eq!(puzzle.part1(), 95437);
-    assert_eq!(puzzle.part2(), 24933642);
+fn fuzz() {
+    aoc_core::testing::check_fuzz::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test malformed input
+#[test]
+fn malformed() {
+    let error = "$ cd /\n$ ls\ndir a\n$ cd b\n"
+        .parse::<Puzzle>()
+        .err()
+        .unwrap();
+    assert_eq!((error.line, error.column), (4, 6));
+
+    let error = "$ cd /\n$ ls\n40000000 a\n40000000 b\n"
+        .parse::<Puzzle>()
+        .err()
+        .unwrap();
+    assert_eq!((error.line, error.column), (4, 1));
+}
+
+/// Test every malformed line of the transcript is reported
+#[test]
+fn linted() {
+    aoc_core::testing::check_lint::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+
+    let errors = Puzzle::lint("$ cd /\n$ ls\ndir a\n$ cd b\n$ cd ..\n$ cd a\n$ mkdir c\n12 \n");
+    let positions = errors
+        .iter()
+        .map(|e| (e.line, e.column))
+        .collect::<Vec<_>>();
+    assert_eq!(positions, [(4, 6), (7, 3), (8, 4)]);
+
+    let errors = Puzzle::lint("$ cd /\n$ cd ..\n");
+    assert_eq!((errors[0].line, errors[0].column), (2, 6));
+    assert_eq!(errors[0].found, "`..`");
+}
+
+/// Test the directories entered and their sizes are traced
+#[test]
+fn traced() {
+    let input = "$ cd /\n$ ls\ndir a\n10 b.txt\n$ cd a\n$ ls\n20 c\n$ cd ..\n";
+    let mut recorder = aoc_core::observe::Recorder::default();
+    assert_eq!(Puzzle::observe(input, 1, &mut recorder).unwrap(), "50");
+    assert_eq!(
+        recorder.events,
+        [
+            r#"{"event":"Cd","path":"/"}"#,
+            r#"{"event":"Cd","path":"/a/"}"#,
+            r#"{"event":"Cd","path":"/"}"#,
+            r#"{"event":"Size","path":"/","size":30}"#,
+            r#"{"event":"Size","path":"/a/","size":20}"#
+        ]
+    );
+}
+
+/// Test the sizes of the filesystem, the update and small directories can be changed
+#[test]
+fn params() {
+    let input = std::fs::read_to_string("test01.txt").unwrap();
+    let params = Params {
+        filesystem_size: 50_000_000,
+        update_size: 10_000_000,
+        small_directory_size: 1000,
+    };
+    let puzzle = Puzzle::from_input_with(&input, params.clone()).unwrap();
+    assert_eq!((puzzle.part1(), puzzle.part2()), (584, 24933642));
+    assert_eq!(puzzle.reference_part2(), 24933642);
+
+    // Files larger than the filesystem are rejected
+    let params = Params {
+        filesystem_size: 40_000_000,
+        ..params
+    };
+    let error = Puzzle::from_input_with(&input, params).err().unwrap();
+    assert_eq!((error.line, error.column), (22, 1));
 }
 
-/// Test from user input
+/// Test the directories behind the answers are found
 #[test]
-fn test02() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test02.txt");
-    assert_eq!(puzzle.part1(), 1297159);
-    assert_eq!(puzzle.part2(), 3866390);
-}
-
-fn main() {
-    let mut puzzle = Puzzle::new();
-    let input = std::env::args().nth(1).expect("No input file");
-    puzzle.configure(&input);
-    println!("{}", puzzle.part1());
-    println!("{}", puzzle.part2());
+fn explained() {
+    let puzzle = std::fs::read_to_string("test01.txt")
+        .unwrap()
+        .parse::<Puzzle>()
+        .unwrap();
+    assert_eq!(puzzle.small_directories(), [("/a/", 94853), ("/a/e/", 584)]);
+    let deletion = puzzle.deletion().unwrap();
+    assert_eq!((deletion.path.as_str(), deletion.size), ("/d/", 24933642));
+    assert_eq!(
+        puzzle.explain(2),
+        "delete /d/ of size 24933642, 8381165 is needed\n"
+    );
 }
@@ -0,0 +1,3 @@
+fn main() {
+    aoc_core::main::<day07::Puzzle>();
+}
@@ -2,12 +2,44 @@
 //! [Day 8: Treetop Tree House](https://adventofcode.com/2022/day/8)
 //!
 
-struct Puzzle {
-    rows: Vec<Vec<u32>>,
-    columns: Vec<Vec<u32>>,
+#![warn(missing_docs)]
+
+use aoc_core::image::{self, Rgb};
+use aoc_core::observe::event;
+use aoc_core::parse::Issues;
+use aoc_core::{
+    Day, Explain, Generate, Grid, Lint, Observe, Observer, ParseError, Reference, Render, Rng,
+    Solution,
+};
+use std::str::FromStr;
+
+/// Heights of the trees of the forest
+pub struct Puzzle {
+    trees: Grid<u32>,
+}
+
+/// The day as run by `aor`
+pub const DAY: Day = Day::new::<Puzzle>(8, "Treetop Tree House")
+    .with_render::<Puzzle>()
+    .with_observe::<Puzzle>()
+    .with_lint::<Puzzle>()
+    .with_explain::<Puzzle>();
+
+/// Tree with the highest scenic score, as found by [`Puzzle::best_tree`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BestTree {
+    /// Column of the tree, from 0 on the left
+    pub x: usize,
+    /// Row of the tree, from 0 at the top
+    pub y: usize,
+    /// Height of the tree, from 0 to 9
+    pub height: u32,
+    /// Product of the viewing distances in the four directions
+    pub score: u32,
 }
 
-fn is_visible(index: usize, line: &[u32]) -> bool {
+/// Whether the tree at an index of a row or column of heights is seen from one of its ends
+pub fn is_visible(index: usize, line: &[u32]) -> bool {
     // Check `[0, index)`
     if let Some(max) = line.get(..index).unwrap().iter().max() {
         if *max < line[index] {
@@ -29,7 +61,9 @@ fn is_visible(index: usize, line: &[u32]) -> bool {
     false
 }
 
-fn scenic(index: usize, line: &[u32]) -> u32 {
+/// Product of the viewing distances from the tree at an index of a row or column, towards both
+/// of its ends
+pub fn scenic(index: usize, line: &[u32]) -> u32 {
     let mut begin_score = 0;
     for i in 1..=index {
         begin_score += 1;
@@ -49,82 +83,306 @@ fn scenic(index: usize, line: &[u32]) -> u32 {
     begin_score * end_score
 }
 
-impl Puzzle {
-    fn new() -> Self {
-        Self {
-            rows: Vec::new(),
-            columns: Vec::new(),
-        }
+impl Solution for Puzzle {
+    type Part1 = usize;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut issues = Issues::default();
+        let puzzle = Self::read(input, &mut issues);
+        issues.first(puzzle)
     }
 
-    fn configure(&mut self, path: &str) {
-        let mut data = std::fs::read_to_string(path).unwrap();
-        data.pop();
+    fn part1(&self) -> usize {
+        self.visible_trees(&mut ())
+    }
 
-        // Initialize columns
-        for _ in 0..data.find('\n').unwrap() {
-            self.columns.push(Vec::new());
-        }
+    fn part2(&self) -> u32 {
+        self.best_tree(&mut ()).map_or(0, |tree| tree.score)
+    }
+}
 
-        // Do two representations of the forest, on by rows and the other by columns
-        for line in data.split('\n') {
-            self.rows.push(Vec::new());
-            for (index, c) in line.chars().enumerate() {
-                let val = c.to_digit(10).unwrap();
-                self.rows.last_mut().unwrap().push(val);
-                self.columns[index].push(val);
-            }
-        }
+impl Lint for Puzzle {
+    fn lint(input: &str) -> Vec<ParseError> {
+        let mut issues = Issues::default();
+        Self::read(input, &mut issues);
+        issues.errors()
     }
+}
 
-    fn part1(&self) -> usize {
+impl Puzzle {
+    /// Parse the heights, going on after any tree or row in error
+    fn read(input: &str, issues: &mut Issues) -> Option<Self> {
+        let trees = Grid::parse_checked(input, "a tree height", |_, c| c.to_digit(10), issues)?;
+        Some(Self { trees })
+    }
+
+    /// Height of each tree, from 0 to 9
+    pub fn trees(&self) -> &Grid<u32> {
+        &self.trees
+    }
+
+    /// Count of trees visible from outside of the forest
+    pub fn visible_trees(&self, observer: &mut dyn Observer) -> usize {
         let mut visible_tree_count = 0;
-        for r in 0..self.rows.len() {
-            for c in 0..self.rows[r].len() {
-                if is_visible(c, &self.rows[r]) || is_visible(r, &self.columns[c]) {
+
+        // Rows are slices of the grid, a column is copied once to be seen the same way
+        for c in 0..self.trees.width() {
+            let column = self.trees.column(c).copied().collect::<Vec<_>>();
+            for r in 0..self.trees.height() {
+                let visible = is_visible(c, self.trees.row(r)) || is_visible(r, &column);
+                if visible {
                     visible_tree_count += 1;
                 }
+                observer.trace(|| {
+                    event("Tree")
+                        .value("x", c)
+                        .value("y", r)
+                        .value("visible", visible)
+                });
             }
         }
         visible_tree_count
     }
 
-    fn part2(&self) -> u32 {
-        let mut scenic_max = 0;
+    /// Tree with the highest scenic score, the first one column after column among trees of the
+    /// same score. There is none in a forest without trees inside, which all score 0
+    pub fn best_tree(&self, observer: &mut dyn Observer) -> Option<BestTree> {
+        let mut best: Option<BestTree> = None;
 
         // Ignore borders during iteration, scenic score is null
-        for r in 1..self.rows.len() - 1 {
-            for c in 1..self.rows[r].len() - 1 {
-                scenic_max = scenic_max.max(scenic(c, &self.rows[r]) * scenic(r, &self.columns[c]));
+        for c in 1..self.trees.width().saturating_sub(1) {
+            let column = self.trees.column(c).copied().collect::<Vec<_>>();
+            for r in 1..self.trees.height() - 1 {
+                let score = scenic(c, self.trees.row(r)) * scenic(r, &column);
+                if best.is_none_or(|best| score > best.score) {
+                    best = Some(BestTree {
+                        x: c,
+                        y: r,
+                        height: self.trees[(c, r)],
+                        score,
+                    });
+                }
+                observer.trace(|| {
+                    event("Scenic")
+                        .value("x", c)
+                        .value("y", r)
+                        .value("score", score)
+                });
             }
         }
 
-        scenic_max
+        best
+    }
+}
+
+impl Observe for Puzzle {
+    /// A `Tree` event telling whether each tree is visible in the first part, a `Scenic` event
+    /// with the score of each tree inside the forest in the second one. Trees are taken column
+    /// after column
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError> {
+        let puzzle = Self::from_input(input)?;
+        Ok(match part {
+            1 => puzzle.visible_trees(observer).to_string(),
+            _ => puzzle
+                .best_tree(observer)
+                .map_or(0, |tree| tree.score)
+                .to_string(),
+        })
+    }
+}
+
+impl Explain for Puzzle {
+    /// How many visible trees are on the edge in the first part, where the best tree stands in
+    /// the second one
+    fn explain(&self, part: u32) -> String {
+        if part == 1 {
+            let (width, height) = (self.trees.width(), self.trees.height());
+            let edge = if width <= 2 || height <= 2 {
+                width * height
+            } else {
+                2 * (width + height) - 4
+            };
+            let inside = self.visible_trees(&mut ()) - edge;
+            return format!("{edge} tree(s) visible on the edge and {inside} inside the forest\n");
+        }
+        match self.best_tree(&mut ()) {
+            Some(tree) => format!(
+                "tree of height {} at x={}, y={} (from 0), scenic score {}\n",
+                tree.height, tree.x, tree.y, tree.score
+            ),
+            None => String::from("no tree inside the forest\n"),
+        }
+    }
+}
+
+impl Generate for Puzzle {
+    /// Forest of `size` rows of `size` trees
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut input = String::new();
+        for _ in 0..size {
+            for _ in 0..size {
+                input.push(char::from(b'0' + rng.below(10) as u8));
+            }
+            input.push('\n');
+        }
+        input
     }
 }
 
-/// Test from puzzle input
+impl Render for Puzzle {
+    /// Trees visible from outside of the forest in green and hidden ones in gray, taller trees
+    /// being brighter
+    fn render(&self) -> Grid<Rgb> {
+        let columns = (0..self.trees.width())
+            .map(|c| self.trees.column(c).copied().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        Grid::from_fn(self.trees.width(), self.trees.height(), |(c, r)| {
+            let [level, ..] = image::gray(u64::from(self.trees[(c, r)]) + 1, 10);
+            if is_visible(c, self.trees.row(r)) || is_visible(r, &columns[c]) {
+                [level / 4, level, level / 4]
+            } else {
+                [level; 3]
+            }
+        })
+    }
+}
+
+/// Directions to look at from a tree, as column and row steps
+const DIRECTIONS: [(isize, isize); 4] = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+
+impl Puzzle {
+    /// Heights of the trees seen from a position in a direction, nearest first
+    fn line_of_sight(&self, (x, y): (usize, usize), direction: (isize, isize)) -> Vec<u32> {
+        let mut heights = Vec::new();
+        let mut position = (x, y);
+        while let (Some(x), Some(y)) = (
+            position.0.checked_add_signed(direction.0),
+            position.1.checked_add_signed(direction.1),
+        ) {
+            match self.trees.get((x, y)) {
+                Some(&height) => heights.push(height),
+                None => break,
+            }
+            position = (x, y);
+        }
+        heights
+    }
+}
+
+impl Reference for Puzzle {
+    fn reference_part1(&self) -> usize {
+        let mut result = 0;
+        for (position, &height) in self.trees.iter() {
+            if DIRECTIONS.iter().any(|&direction| {
+                self.line_of_sight(position, direction)
+                    .iter()
+                    .all(|&other| other < height)
+            }) {
+                result += 1;
+            }
+        }
+        result
+    }
+
+    fn reference_part2(&self) -> u32 {
+        let mut result = 0;
+        for (position, &height) in self.trees.iter() {
+            let score = DIRECTIONS
+                .iter()
+                .map(|&direction| {
+                    let line = self.line_of_sight(position, direction);
+                    match line.iter().position(|&other| other >= height) {
+                        Some(blocking) => blocking as u32 + 1,
+                        None => line.len() as u32,
+                    }
+                })
+                .product();
+            result = result.max(score);
+        }
+        result
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s)
+    }
+}
+
+/// Test every example input against its manifest
+#[test]
+fn examples() {
+    aoc_core::testing::check_examples::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test inputs of the generator
+#[test]
+fn generated() {
+    aoc_core::testing::check_generated::<Puzzle>();
+}
+
+/// Compare with the reference solution on generated inputs
+#[test]
+fn reference() {
+    aoc_core::testing::check_reference::<Puzzle>();
+}
+
+/// Test mutated inputs never panic
 #[test]
-fn test01() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test01.txt");
-    assert_eq!(puzzle.part1(), 21);
-    assert_eq!(puzzle.part2(), 8);
+fn fuzz() {
+    aoc_core::testing::check_fuzz::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test the best tree is located
+#[test]
+fn explained() {
+    let puzzle = std::fs::read_to_string("test01.txt")
+        .unwrap()
+        .parse::<Puzzle>()
+        .unwrap();
+    let tree = puzzle.best_tree(&mut ()).unwrap();
+    assert_eq!((tree.x, tree.y, tree.height, tree.score), (2, 3, 5, 8));
+    assert_eq!(
+        puzzle.explain(1),
+        "16 tree(s) visible on the edge and 5 inside the forest\n"
+    );
+    assert_eq!(
+        "12\n34\n".parse::<Puzzle>().unwrap().best_tree(&mut ()),
+        None
+    );
+}
+
+/// Test malformed input
+#[test]
+fn malformed() {
+    let error = "303\n2x5\n".parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (2, 2));
+}
+
+/// Test every tree that is not a digit and every row of the wrong length is reported
+#[test]
+fn linted() {
+    aoc_core::testing::check_lint::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+
+    let errors = Puzzle::lint("303\n2x5\n65\n3a4b\n");
+    let positions = errors
+        .iter()
+        .map(|e| (e.line, e.column))
+        .collect::<Vec<_>>();
+    assert_eq!(positions, [(2, 2), (3, 3), (4, 2), (4, 4)]);
 }
 
-/// Test from user input
+/// Test visible trees are told apart from hidden ones
 #[test]
-fn test02() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test02.txt");
-    assert_eq!(puzzle.part1(), 1849);
-    assert_eq!(puzzle.part2(), 201600);
-}
-
-fn main() {
-    let mut puzzle = Puzzle::new();
-    let input = std::env::args().nth(1).expect("No input file");
-    puzzle.configure(&input);
-    println!("{}", puzzle.part1());
-    println!("{}", puzzle.part2());
+fn rendered() {
+    let picture = "30373\n25512\n65332\n33549\n35390\n"
+        .parse::<Puzzle>()
+        .unwrap()
+        .render();
+    assert_eq!((picture.width(), picture.height()), (5, 5));
+    assert_eq!(picture[(0, 0)], [25, 102, 25]);
+    assert_eq!(picture[(3, 1)], [51; 3]);
 }
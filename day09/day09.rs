@@ -2,119 +2,327 @@
 //! [Day 9: Rope Bridge](https://adventofcode.com/2022/day/9)
 //!
 
+#![warn(missing_docs)]
+
+use aoc_core::observe::{event, point};
+use aoc_core::params::{self, Value};
+use aoc_core::{
+    parse, Bounds, Configure, Day, Direction, Generate, Observe, Observer, ParseError, Point,
+    Reference, Rng, Solution,
+};
 use std::collections::HashSet;
+use std::str::FromStr;
+
+/// Steps the head can make in total, which keeps simulations short and positions in range
+const MAX_STEPS: usize = 10_000_000;
 
-struct Puzzle {
-    motions: Vec<char>,
+/// Constants of the puzzle
+#[derive(Clone, Debug)]
+pub struct Params {
+    /// Count of knots of the rope of the first part
+    pub short_rope: usize,
+    /// Count of knots of the rope of the second part
+    pub long_rope: usize,
 }
 
-impl Puzzle {
-    fn new() -> Self {
+impl Default for Params {
+    fn default() -> Self {
         Self {
-            motions: Vec::new(),
+            short_rope: 2,
+            long_rope: 10,
+        }
+    }
+}
+
+impl params::Params for Params {
+    fn fields(&mut self) -> Vec<(&'static str, &mut dyn Value)> {
+        vec![
+            ("short_rope", &mut self.short_rope),
+            ("long_rope", &mut self.long_rope),
+        ]
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if self.short_rope == 0 || self.long_rope == 0 {
+            return Err(String::from("ropes have at least one knot"));
         }
+        Ok(())
+    }
+}
+
+/// Motions of the head of the rope, split into single steps
+pub struct Puzzle {
+    motions: Vec<Direction>,
+    params: Params,
+}
+
+/// The day as run by `aor`
+pub const DAY: Day = Day::new::<Puzzle>(9, "Rope Bridge")
+    .with_observe::<Puzzle>()
+    .with_params::<Puzzle>();
+
+impl Puzzle {
+    /// Direction of each step of the head
+    pub fn motions(&self) -> &[Direction] {
+        &self.motions
     }
+}
+
+/// Count of the positions visited by the tail of a rope of `length` knots, at least one, as its
+/// head moves one step at a time from the origin
+pub fn simulate_rope(motions: &[Direction], length: usize, observer: &mut dyn Observer) -> usize {
+    assert!(length != 0, "a rope without knots");
+    let mut knots_pos = vec![Point::ORIGIN; length];
+
+    let mut positions = HashSet::new();
+    positions.insert(Point::ORIGIN);
+
+    for direction in motions {
+        knots_pos[0] += direction.step();
 
-    fn configure(&mut self, path: &str) {
-        let mut data = std::fs::read_to_string(path).unwrap();
-        data.pop();
+        // Iterate over pairs of knots. The first acting like an head and the other like a tail
+        for index in 0..length - 1 {
+            let head = knots_pos[index];
+            let tail = knots_pos.get_mut(index + 1).unwrap();
 
-        for line in data.split('\n') {
-            let (direction, count) = line.split_once(' ').unwrap();
-            let (direction, count) = (
-                direction.parse::<char>().unwrap(),
-                count.parse::<usize>().unwrap(),
-            );
+            // Check if `head` is on the 5x5 square around `tail`
+            if (head.y - tail.y).abs() == 2 && (head.x - tail.x).abs() == 2 {
+                tail.x = (tail.x + head.x) / 2;
+                tail.y = (tail.y + head.y) / 2;
+            } else if (head.y - tail.y).abs() == 2 {
+                tail.x = head.x;
+                tail.y = (tail.y + head.y) / 2;
+            } else if (head.x - tail.x).abs() == 2 {
+                tail.x = (tail.x + head.x) / 2;
+                tail.y = head.y;
+            } else {
+                // This knot does not move, there is no need to check the others
+                break;
+            }
+        }
+        positions.insert(*knots_pos.last().unwrap());
+        observer.trace(|| {
+            event("Step")
+                .string("direction", &format!("{direction:?}"))
+                .raw("head", &point(knots_pos[0]))
+                .raw("tail", &point(knots_pos[length - 1]))
+        });
+        if observer.step() {
+            observer.frame(&draw(&knots_pos, &positions));
+        }
+    }
+
+    positions.len()
+}
+
+impl Solution for Puzzle {
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Self::parse_with(input, Params::default())
+    }
+
+    fn part1(&self) -> usize {
+        simulate_rope(&self.motions, self.params.short_rope, &mut ())
+    }
+
+    fn part2(&self) -> usize {
+        simulate_rope(&self.motions, self.params.long_rope, &mut ())
+    }
+}
+
+impl Configure for Puzzle {
+    type Params = Params;
+
+    fn parse_with(input: &str, params: Params) -> Result<Self, ParseError> {
+        let mut motions = Vec::new();
+        for line in parse::lines(input) {
+            let mut scanner = line.scan();
+            let letter = scanner.one_of(&['U', 'D', 'L', 'R'])?;
+            let direction = Direction::from_letter(letter).expect("a direction letter");
+            scanner.tag(" ")?;
+            let start = scanner.clone();
+            let count = scanner.number::<usize>()?;
+            if count > MAX_STEPS - motions.len() {
+                return Err(start.error(format!("at most {MAX_STEPS} steps in total")));
+            }
+            scanner.end()?;
 
             // Split a motion into several 1-step motions
             for _ in 0..count {
-                self.motions.push(direction);
+                motions.push(direction);
             }
         }
+
+        Ok(Self { motions, params })
     }
+}
 
-    fn move_rope(&self, length: usize) -> usize {
-        let mut knots_pos: Vec<(i32, i32)> = Vec::new();
-        knots_pos.resize(length, (0, 0));
+impl Observe for Puzzle {
+    /// A `Step` event and a frame of the rope after each step of the head
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError> {
+        let puzzle = Self::from_input(input)?;
+        let length = match part {
+            1 => puzzle.params.short_rope,
+            _ => puzzle.params.long_rope,
+        };
+        Ok(simulate_rope(&puzzle.motions, length, observer).to_string())
+    }
+}
 
-        let mut positions = HashSet::new();
-        positions.insert((0, 0));
+/// Drawing of the knots over the positions visited by the tail: `H` for the head, the index of
+/// the other knots, `s` for the start and `#` for the positions visited
+fn draw(knots: &[Point], visited: &HashSet<Point>) -> String {
+    let bounds = Bounds::of(visited.iter().chain(knots).copied()).unwrap();
+    let mut drawing = String::new();
+    for y in bounds.min.y..=bounds.max.y {
+        for x in bounds.min.x..=bounds.max.x {
+            let point = Point::new(x, y);
+            drawing.push(match knots.iter().position(|&knot| knot == point) {
+                Some(0) => 'H',
+                Some(index) => char::from_digit(index as u32, 36).unwrap_or('T'),
+                None if point == Point::ORIGIN => 's',
+                None if visited.contains(&point) => '#',
+                None => '.',
+            });
+        }
+        drawing.push('\n');
+    }
+    drawing
+}
 
-        for direction in &self.motions {
-            let head_position = knots_pos.get_mut(0).unwrap();
-            match direction {
-                'U' => head_position.1 += 1,
-                'D' => head_position.1 -= 1,
-                'L' => head_position.0 -= 1,
-                'R' => head_position.0 += 1,
-                _ => panic!("bad input"),
-            }
+impl Generate for Puzzle {
+    /// `size` motions of the head
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut input = String::new();
+        for _ in 0..size {
+            let direction = rng.pick(&['U', 'D', 'L', 'R']);
+            input.push_str(&format!("{direction} {}\n", rng.range(1..=20)));
+        }
+        input
+    }
+}
 
-            // Iterate over pairs of knots. The first acting like an head and the other like a tail
-            for index in 0..length - 1 {
-                let head = knots_pos[index];
-                let tail = knots_pos.get_mut(index + 1).unwrap();
-
-                // Check if `head` is on the 5x5 square around `tail`
-                if (head.1 - tail.1).abs() == 2 && (head.0 - tail.0).abs() == 2 {
-                    tail.0 = (tail.0 + head.0) / 2;
-                    tail.1 = (tail.1 + head.1) / 2;
-                } else if (head.1 - tail.1).abs() == 2 {
-                    tail.0 = head.0;
-                    tail.1 = (tail.1 + head.1) / 2;
-                } else if (head.0 - tail.0).abs() == 2 {
-                    tail.0 = (tail.0 + head.0) / 2;
-                    tail.1 = head.1;
-                } else {
-                    // This knot does not move, there is no need to check the others
-                    break;
+impl Puzzle {
+    /// Simulate the rope with each knot stepping towards the previous one when they stop touching
+    fn follow(&self, length: usize) -> usize {
+        let mut knots = vec![Point::ORIGIN; length];
+        let mut visited = HashSet::from([Point::ORIGIN]);
+        for direction in &self.motions {
+            knots[0] += direction.step();
+            for index in 1..length {
+                let (head, tail) = (knots[index - 1], knots[index]);
+                if head.chebyshev(tail) > 1 {
+                    knots[index] += (head - tail).signum();
                 }
             }
-            positions.insert(*knots_pos.last().unwrap());
+            visited.insert(knots[length - 1]);
         }
+        visited.len()
+    }
+}
 
-        positions.len()
+impl Reference for Puzzle {
+    fn reference_part1(&self) -> usize {
+        self.follow(self.params.short_rope)
     }
 
-    fn part1(&self) -> usize {
-        self.move_rope(2)
+    fn reference_part2(&self) -> usize {
+        self.follow(self.params.long_rope)
     }
+}
 
-    fn part2(&self) -> usize {
-        self.move_rope(10)
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s)
     }
 }
 
-/// Test from puzzle input
+/// Test every example input against its manifest
+#[test]
+fn examples() {
+    aoc_core::testing::check_examples::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test inputs of the generator
+#[test]
+fn generated() {
+    aoc_core::testing::check_generated::<Puzzle>();
+}
+
+/// Compare with the reference solution on generated inputs
+#[test]
+fn reference() {
+    aoc_core::testing::check_reference::<Puzzle>();
+}
+
+/// Test mutated inputs never panic
 #[test]
-fn test01() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test01.txt");
-    assert_eq!(puzzle.part1(), 13);
-    assert_eq!(puzzle.part2(), 1);
+fn fuzz() {
+    aoc_core::testing::check_fuzz::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test longer ropes never have their tail visiting more positions
+#[test]
+fn properties() {
+    aoc_core::testing::check_property::<Puzzle>("knots-visits", |puzzle| {
+        let visited = (2..=10)
+            .map(|length| simulate_rope(&puzzle.motions, length, &mut ()))
+            .collect::<Vec<_>>();
+        for (length, pair) in (2..).zip(visited.windows(2)) {
+            if pair[1] > pair[0] {
+                return Err(format!(
+                    "{} positions with {length} knots, {} with {}",
+                    pair[0],
+                    pair[1],
+                    length + 1
+                ));
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Test malformed input
+#[test]
+fn malformed() {
+    let error = "R 4\nX 4\n".parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (2, 1));
+
+    let error = "R 4\nU 9999999\n".parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (2, 3));
 }
 
-/// Test from puzzle input
+/// Test the rope is drawn and traced after every step of the head
 #[test]
-fn test02() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test02.txt");
-    assert_eq!(puzzle.part2(), 36);
+fn animated() {
+    let mut recorder = aoc_core::observe::Recorder::default();
+    assert_eq!(
+        Puzzle::observe("R 4\nU 2\n", 1, &mut recorder).unwrap(),
+        "5"
+    );
+    assert_eq!(recorder.frames.len(), 6);
+    assert_eq!(recorder.frames[0], "1H\n");
+    assert_eq!(recorder.frames[5], "....H\n....1\ns###.\n");
+    assert_eq!(
+        recorder.events[5],
+        r#"{"event":"Step","direction":"Up","head":[4,-2],"tail":[4,-1]}"#
+    );
 }
 
-/// Test from user input
+/// Test the lengths of the ropes can be changed
 #[test]
-fn test03() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test03.txt");
-    assert_eq!(puzzle.part1(), 5883);
-    assert_eq!(puzzle.part2(), 2367);
-}
-
-fn main() {
-    let mut puzzle = Puzzle::new();
-    let input = std::env::args().nth(1).expect("No input file");
-    puzzle.configure(&input);
-    println!("{}", puzzle.part1());
-    println!("{}", puzzle.part2());
+fn params() {
+    let input = std::fs::read_to_string("test01.txt").unwrap();
+    let params = Params {
+        short_rope: 1,
+        long_rope: 3,
+    };
+    let puzzle = Puzzle::from_input_with(&input, params).unwrap();
+    assert_eq!(puzzle.part1(), puzzle.reference_part1());
+    assert_eq!(puzzle.part2(), puzzle.reference_part2());
+    assert_eq!(puzzle.part1(), 21);
 }
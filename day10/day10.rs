@@ -2,110 +2,312 @@
 //! [Day 10: Cathode-Ray Tube](https://adventofcode.com/2022/day/10)
 //!
 
-struct Puzzle {
-    instructions: Vec<i32>,
+#![warn(missing_docs)]
+
+use aoc_core::observe::event;
+use aoc_core::params::{self, Value};
+use aoc_core::{
+    parse, Configure, Day, Generate, Observe, Observer, ParseError, Reference, Rng, Solution,
+};
+use std::str::FromStr;
+
+/// Constants of the puzzle
+#[derive(Clone, Debug)]
+pub struct Params {
+    /// Cycles during which the signal strength is measured in the first part, in increasing order
+    /// from the first one
+    pub cycles: Vec<u32>,
 }
 
-impl Puzzle {
-    fn new() -> Self {
+impl Default for Params {
+    fn default() -> Self {
         Self {
-            instructions: Vec::new(),
+            cycles: vec![20, 60, 100, 140, 180, 220],
         }
     }
+}
+
+impl params::Params for Params {
+    fn fields(&mut self) -> Vec<(&'static str, &mut dyn Value)> {
+        vec![("cycles", &mut self.cycles)]
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if self.cycles.first() == Some(&0) || self.cycles.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(String::from(
+                "cycles are listed in increasing order from the first one",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Program run by the CPU of the device
+pub struct Puzzle {
+    instructions: Vec<i32>,
+    params: Params,
+}
+
+/// The day as run by `aor`
+pub const DAY: Day = Day::new::<Puzzle>(10, "Cathode-Ray Tube")
+    .with_observe::<Puzzle>()
+    .with_params::<Puzzle>();
+
+impl Solution for Puzzle {
+    type Part1 = i64;
+    type Part2 = String;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Self::parse_with(input, Params::default())
+    }
+
+    fn part1(&self) -> i64 {
+        self.signal_strength(&mut ())
+    }
+
+    fn part2(&self) -> String {
+        self.crt(&mut ())
+    }
+}
 
-    fn configure(&mut self, path: &str) {
-        let mut data = std::fs::read_to_string(path).unwrap();
-        data.pop();
+impl Configure for Puzzle {
+    type Params = Params;
 
+    fn parse_with(input: &str, params: Params) -> Result<Self, ParseError> {
         // The idea applied here is to make each entry in the vector count as one cycle
-        for line in data.split('\n') {
-            if line == "noop" {
-                self.instructions.push(0);
-            } else {
+        let mut instructions = Vec::new();
+        for line in parse::lines(input) {
+            let mut scanner = line.scan();
+            if scanner.eat("noop") {
+                instructions.push(0);
+            } else if scanner.eat("addx ") {
                 // Add an empty instruction to simulate the first cycle
-                self.instructions.push(0);
-                let (_, val) = line.split_once(' ').unwrap();
-                self.instructions.push(val.parse::<i32>().unwrap());
+                instructions.push(0);
+                instructions.push(scanner.number::<i32>()?);
+            } else {
+                return Err(scanner.error("`noop` or `addx`"));
             }
+            scanner.end()?;
         }
+
+        Ok(Self {
+            instructions,
+            params,
+        })
     }
+}
 
-    fn part1(&self) -> i32 {
+impl Puzzle {
+    /// Value added to the X register at the end of each cycle, `addx` taking two of them
+    pub fn instructions(&self) -> &[i32] {
+        &self.instructions
+    }
+
+    /// Sum of the signal strengths during the cycles of interest
+    pub fn signal_strength(&self, observer: &mut dyn Observer) -> i64 {
+        // Values are summed on 64 bits, they cannot overflow whatever the instructions
         let mut signal_strength = 0;
-        let mut register_value: i32 = 1;
+        let mut register_value: i64 = 1;
 
         for (index, value) in self.instructions.iter().enumerate() {
-            let cycle = i32::try_from(index).unwrap() + 1;
-            if [20, 60, 100, 140, 180, 220].contains(&cycle) {
+            let cycle = index as i64 + 1;
+            if u32::try_from(cycle).is_ok_and(|cycle| self.params.cycles.contains(&cycle)) {
                 signal_strength += register_value * cycle;
             }
-            register_value += value;
+            observer.trace(|| {
+                event("Cycle")
+                    .value("cycle", cycle)
+                    .value("x", register_value)
+            });
+            if observer.step() {
+                observer.frame(&format!(
+                    "cycle {cycle}\nX = {register_value}\nsignal strength {signal_strength}\n"
+                ));
+            }
+            register_value += i64::from(*value);
         }
 
         signal_strength
     }
 
-    fn part2(&self) -> String {
-        let mut sprite_position = 2;
+    /// Picture drawn on the CRT, with a line per row
+    pub fn crt(&self, observer: &mut dyn Observer) -> String {
+        let mut sprite_position: i64 = 2;
         let mut row_offset = 0;
         let mut crt = String::new();
 
         for (index, value) in self.instructions.iter().enumerate() {
-            let cycle = i32::try_from(index).unwrap() + 1 - row_offset;
+            let cycle = index as i64 + 1 - row_offset;
             if (sprite_position - 1..=sprite_position + 1).contains(&cycle) {
                 crt.push('#');
             } else {
                 crt.push('.');
             }
+            observer.trace(|| {
+                event("Pixel")
+                    .value("cycle", index + 1)
+                    .value("sprite", sprite_position - 1)
+                    .value("lit", crt.ends_with('#'))
+            });
             if cycle == 40 {
                 crt.push('\n');
                 row_offset += 40;
             }
-            sprite_position += value;
+            if observer.step() {
+                observer.frame(&crt);
+            }
+            sprite_position += i64::from(*value);
         }
 
         crt
     }
 }
 
-/// Test from puzzle input
+impl Observe for Puzzle {
+    /// An event and a frame after each cycle: a `Cycle` with the register and the signal strength
+    /// in the first part, a `Pixel` drawn on the CRT in the second one
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError> {
+        let puzzle = Self::from_input(input)?;
+        Ok(match part {
+            1 => puzzle.signal_strength(observer).to_string(),
+            _ => puzzle.crt(observer),
+        })
+    }
+}
+
+impl Generate for Puzzle {
+    /// Program of `size` instructions, keeping the sprite around the screen
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut input = String::new();
+        let mut register = 1;
+        for _ in 0..size {
+            if rng.chance(0.3) {
+                input.push_str("noop\n");
+                continue;
+            }
+            let mut value = rng.range(0..=10) as i32 - 5;
+            if !(0..=40).contains(&(register + value)) {
+                value = -value;
+            }
+            register += value;
+            input.push_str(&format!("addx {value}\n"));
+        }
+        input
+    }
+}
+
+impl Puzzle {
+    /// Value of the register during each cycle, starting at the first one
+    fn register_values(&self) -> Vec<i64> {
+        let mut values = Vec::new();
+        let mut register = 1;
+        for &value in &self.instructions {
+            values.push(register);
+            register += i64::from(value);
+        }
+        values
+    }
+}
+
+impl Reference for Puzzle {
+    fn reference_part1(&self) -> i64 {
+        let values = self.register_values();
+        self.params
+            .cycles
+            .iter()
+            .filter_map(|&cycle| {
+                let x = values.get(cycle as usize - 1)?;
+                Some(x * i64::from(cycle))
+            })
+            .sum()
+    }
+
+    fn reference_part2(&self) -> String {
+        let mut crt = String::new();
+        for (index, x) in self.register_values().into_iter().enumerate() {
+            let column = (index % 40) as i64;
+            crt.push(if (x - column).abs() <= 1 { '#' } else { '.' });
+            if column == 39 {
+                crt.push('\n');
+            }
+        }
+        crt
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s)
+    }
+}
+
+/// Test every example input against its manifest
 #[test]
-fn test01() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test01.txt");
-    assert_eq!(puzzle.part1(), 13140);
-
-    let mut expected = String::new();
-    expected.push_str("##..##..##..##..##..##..##..##..##..##..\n");
-    expected.push_str("###...###...###...###...###...###...###.\n");
-    expected.push_str("####....####....####....####....####....\n");
-    expected.push_str("#####.....#####.....#####.....#####.....\n");
-    expected.push_str("######......######......######......####\n");
-    expected.push_str("#######.......#######.......#######.....\n");
-    assert_eq!(puzzle.part2(), expected);
-}
-
-/// Test from user input
+fn examples() {
+    aoc_core::testing::check_examples::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test inputs of the generator
+#[test]
+fn generated() {
+    aoc_core::testing::check_generated::<Puzzle>();
+}
+
+/// Compare with the reference solution on generated inputs
+#[test]
+fn reference() {
+    aoc_core::testing::check_reference::<Puzzle>();
+}
+
+/// Test mutated inputs never panic
+#[test]
+fn fuzz() {
+    aoc_core::testing::check_fuzz::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test malformed input
+#[test]
+fn malformed() {
+    let error = "noop\naddx\n".parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (2, 1));
+}
+
+/// Test a register far out of the screen
+#[test]
+fn overflow() {
+    let input = format!("addx {}\n", i32::MAX).repeat(200);
+    let puzzle = input.parse::<Puzzle>().unwrap();
+    assert_eq!(puzzle.part1(), 121_289_876_383_280);
+}
+
+/// Test the CRT is drawn and traced pixel by pixel
+#[test]
+fn animated() {
+    let input = "addx 15\naddx -11\n";
+    let mut recorder = aoc_core::observe::Recorder::default();
+    assert_eq!(Puzzle::observe(input, 2, &mut recorder).unwrap(), "##..");
+    assert_eq!(recorder.frames, ["#", "##", "##.", "##.."]);
+    assert_eq!(
+        recorder.events[2],
+        r#"{"event":"Pixel","cycle":3,"sprite":16,"lit":false}"#
+    );
+
+    let mut recorder = aoc_core::observe::Recorder::default();
+    Puzzle::observe(input, 1, &mut recorder).unwrap();
+    assert_eq!(recorder.frames[3], "cycle 4\nX = 16\nsignal strength 0\n");
+    assert_eq!(recorder.events[3], r#"{"event":"Cycle","cycle":4,"x":16}"#);
+}
+
+/// Test the cycles of interest can be changed
 #[test]
-fn test02() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test02.txt");
-    assert_eq!(puzzle.part1(), 16060);
-
-    let mut expected = String::new();
-    expected.push_str("###...##...##..####.#..#.#....#..#.####.\n");
-    expected.push_str("#..#.#..#.#..#.#....#.#..#....#..#.#....\n");
-    expected.push_str("###..#..#.#....###..##...#....####.###..\n");
-    expected.push_str("#..#.####.#....#....#.#..#....#..#.#....\n");
-    expected.push_str("#..#.#..#.#..#.#....#.#..#....#..#.#....\n");
-    expected.push_str("###..#..#..##..####.#..#.####.#..#.#....\n");
-    assert_eq!(puzzle.part2(), expected);
-}
-
-fn main() {
-    let mut puzzle = Puzzle::new();
-    let input = std::env::args().nth(1).expect("No input file");
-    puzzle.configure(&input);
-    println!("{}", puzzle.part1());
-    println!("{}", puzzle.part2());
+fn params() {
+    let input = std::fs::read_to_string("test01.txt").unwrap();
+    let params = Params {
+        cycles: vec![1, 20, 500],
+    };
+    let puzzle = Puzzle::from_input_with(&input, params).unwrap();
+    assert_eq!(puzzle.part1(), 1 + 420);
+    assert_eq!(puzzle.reference_part1(), 1 + 420);
 }
@@ -2,158 +2,841 @@
 //! [Day 11: Monkey in the Middle](https://adventofcode.com/2022/day/11)
 //!
 
-#[derive(Clone)]
-struct Monkey {
-    items: Vec<u64>,
-    multiply: bool,
-    worry_operand: Option<u64>,
-    divisibility: u64,
-    true_to: usize,
-    false_to: usize,
+#![warn(missing_docs)]
+
+use aoc_core::observe::event;
+use aoc_core::params::{self, Value};
+use aoc_core::parse::{self, Issues, Line};
+use aoc_core::{
+    Configure, Day, Explain, Generate, Lint, Observe, Observer, ParseError, Reference, Rng,
+    Solution,
+};
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+/// Monkey of the notes, with the items it holds and how it chooses where to throw them
+#[derive(Clone, Debug)]
+pub struct Monkey {
+    /// Worry levels of the items held, the last one is inspected first
+    pub items: Vec<u64>,
+    /// Whether the operation multiplies the worry level, it adds to it otherwise
+    pub multiply: bool,
+    /// Operand of the operation, `None` for the old worry level itself
+    pub worry_operand: Option<u64>,
+    /// Divisor of the test, never zero
+    pub divisibility: u64,
+    /// Monkey an item is thrown to when its worry level is divisible
+    pub true_to: usize,
+    /// Monkey an item is thrown to otherwise
+    pub false_to: usize,
 }
 
 impl Monkey {
-    fn new() -> Self {
-        Self {
-            items: Vec::new(),
-            multiply: false,
-            worry_operand: None,
-            divisibility: 0,
-            true_to: 0,
-            false_to: 0,
-        }
-    }
-
-    fn inspect_and_throw(&self, old: u64, divide: bool) -> (u64, usize) {
-        let val = self.worry_operand.unwrap_or(old);
-        let mut new = if self.multiply { old * val } else { old + val };
-        if divide {
-            new /= 3;
-        }
-        let to = if new % self.divisibility == 0 {
+    /// Worry level after inspecting an item and the monkey it is thrown to, either relief divides
+    /// worry levels or they are reduced by a modulus. Without modulus, they might not fit in 128
+    /// bits
+    pub fn inspect_and_throw(&self, old: u128, modulus: Option<u64>) -> Option<(u128, usize)> {
+        let val = self.worry_operand.map_or(old, u128::from);
+        let new = match modulus {
+            None => {
+                let new = if self.multiply {
+                    old.checked_mul(val)
+                } else {
+                    old.checked_add(val)
+                };
+                new? / 3
+            }
+            Some(modulus) => {
+                // Both operands are below 2^64, so is the modulus
+                let new = if self.multiply { old * val } else { old + val };
+                new % u128::from(modulus)
+            }
+        };
+        let to = if new % u128::from(self.divisibility) == 0 {
             self.true_to
         } else {
             self.false_to
         };
-        (new, to)
+        Some((new, to))
     }
 }
 
-struct Puzzle {
-    monkeys: Vec<Monkey>,
+/// Items inspected by the monkeys, as counted by [`Puzzle::monkey_business`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Business {
+    /// Count of items inspected by each monkey
+    pub inspections: Vec<u64>,
+    /// Indices of the two monkeys that inspected the most items, the busiest first
+    pub busiest: [usize; 2],
 }
 
-impl Puzzle {
-    fn new() -> Self {
+impl Business {
+    /// Level of monkey business, the product of the inspections of the two busiest monkeys, wide
+    /// enough for any count of rounds
+    pub fn level(&self) -> u128 {
+        let [first, second] = self
+            .busiest
+            .map(|index| u128::from(self.inspections[index]));
+        first * second
+    }
+}
+
+/// Constants of the puzzle
+#[derive(Clone, Debug)]
+pub struct Params {
+    /// Rounds of the first part, worry levels being divided by three after each inspection
+    pub relief_rounds: u32,
+    /// Rounds of the second part, without relief
+    pub worry_rounds: u32,
+}
+
+impl Default for Params {
+    fn default() -> Self {
         Self {
-            monkeys: Vec::new(),
+            relief_rounds: 20,
+            worry_rounds: 10000,
         }
     }
+}
+
+impl params::Params for Params {
+    fn fields(&mut self) -> Vec<(&'static str, &mut dyn Value)> {
+        vec![
+            ("relief_rounds", &mut self.relief_rounds),
+            ("worry_rounds", &mut self.worry_rounds),
+        ]
+    }
+}
+
+/// Monkeys and the items they hold
+pub struct Puzzle {
+    monkeys: Vec<Monkey>,
+    /// Error pointing at the operation of each monkey, for worry levels it makes overflow
+    overflows: Vec<ParseError>,
+    params: Params,
+}
+
+/// The day as run by `aor`
+pub const DAY: Day = Day::new::<Puzzle>(11, "Monkey in the Middle")
+    .with_observe::<Puzzle>()
+    .with_params::<Puzzle>()
+    .with_lint::<Puzzle>()
+    .with_explain::<Puzzle>();
 
-    fn configure(&mut self, path: &str) {
-        let mut data = std::fs::read_to_string(path).unwrap();
-        data.pop();
+impl Puzzle {
+    /// Parse the notes, going on after any line in error
+    fn read(input: &str, params: Params, issues: &mut Issues) -> Option<Self> {
+        let blocks = issues.check(parse::blocks(input))?;
 
-        for monkey_configuration in data.split("\n\n") {
-            let lines = monkey_configuration.split('\n').collect::<Vec<_>>();
-            let mut monkey = Monkey::new();
+        // Monkey business needs the two most active monkeys
+        if blocks.len() < 2 {
+            issues.push(parse::end_of_input(input, "another monkey"));
+            return None;
+        }
 
-            monkey.items = lines[1]
-                .split_once("Starting items: ")
-                .unwrap()
-                .1
-                .split(", ")
-                .map(|item| item.parse::<u64>().unwrap())
+        let count = blocks.len();
+        let mut monkeys = Vec::new();
+        let mut overflows = Vec::new();
+        let mut modulus = 1u64;
+        for (index, block) in blocks.iter().enumerate() {
+            if let Some(extra) = block.get(6) {
+                issues.push(extra.error(1, "a blank line"));
+            }
+
+            // Missing lines are reported as blank lines following the monkey
+            let last = block.last().unwrap();
+            let lines = (0..6)
+                .map(|i| {
+                    block.get(i).copied().unwrap_or(Line {
+                        number: last.number + i + 1 - block.len(),
+                        text: "",
+                    })
+                })
                 .collect::<Vec<_>>();
 
-            monkey.multiply = lines[2].contains('*');
-            monkey.worry_operand = lines[2].split(' ').last().unwrap().parse::<u64>().ok();
-
-            monkey.divisibility = lines[3]
-                .split_once("Test: divisible by ")
-                .unwrap()
-                .1
-                .parse::<u64>()
-                .unwrap();
-
-            monkey.true_to = lines[4]
-                .split_once("If true: throw to monkey ")
-                .unwrap()
-                .1
-                .parse::<usize>()
-                .unwrap();
-
-            monkey.false_to = lines[5]
-                .split_once("If false: throw to monkey ")
-                .unwrap()
-                .1
-                .parse::<usize>()
-                .unwrap();
-
-            self.monkeys.push(monkey);
+            let header = issues.check(Self::parse_header(&lines[0], index));
+            let items = issues.check(Self::parse_items(&lines[1]));
+            let operation = issues.check(Self::parse_operation(&lines[2]));
+            let divisibility = issues.check(Self::parse_test(&lines[3], &mut modulus));
+            let true_to = issues.check(Self::parse_target(
+                &lines[4],
+                "    If true: throw to monkey ",
+                index,
+                count,
+            ));
+            let false_to = issues.check(Self::parse_target(
+                &lines[5],
+                "    If false: throw to monkey ",
+                index,
+                count,
+            ));
+            if let (
+                Some(()),
+                Some(items),
+                Some((multiply, worry_operand, overflow)),
+                Some(divisibility),
+                Some(true_to),
+                Some(false_to),
+            ) = (header, items, operation, divisibility, true_to, false_to)
+            {
+                overflows.push(overflow);
+                monkeys.push(Monkey {
+                    items,
+                    multiply,
+                    worry_operand,
+                    divisibility,
+                    true_to,
+                    false_to,
+                });
+            }
+        }
+        (monkeys.len() == count).then_some(Self {
+            monkeys,
+            overflows,
+            params,
+        })
+    }
+
+    /// Parse the title of the monkey numbered `index`, like `Monkey 0:`
+    fn parse_header(line: &Line, index: usize) -> Result<(), ParseError> {
+        let mut scanner = line.scan();
+        scanner.tag("Monkey ")?;
+        let start = scanner.clone();
+        if scanner.number::<usize>()? != index {
+            return Err(start.error(format!("monkey `{index}`")));
+        }
+        scanner.tag(":")?;
+        scanner.end()
+    }
+
+    /// Parse the worry levels of the items held, like `  Starting items: 79, 98`
+    fn parse_items(line: &Line) -> Result<Vec<u64>, ParseError> {
+        let mut scanner = line.scan();
+        scanner.tag("  Starting items: ")?;
+        let mut items = Vec::new();
+        while !scanner.is_end() {
+            items.push(scanner.number::<u64>()?);
+            if !scanner.eat(", ") {
+                break;
+            }
         }
+        scanner.end()?;
+        Ok(items)
     }
 
-    fn monkey_business(&self, rounds: u32, divide: bool) -> u64 {
-        // Make a local copy of monkeys as their items will be modified
-        let mut monkeys = self.monkeys.clone();
+    /// Parse an operation, like `  Operation: new = old * 19`, into whether it multiplies, its
+    /// operand and the error reported when it makes a worry level overflow
+    fn parse_operation(line: &Line) -> Result<(bool, Option<u64>, ParseError), ParseError> {
+        let mut scanner = line.scan();
+        scanner.tag("  Operation: new = old ")?;
+        let overflow = scanner.error("an operation keeping worry levels in 128 bits");
+        let multiply = scanner.one_of(&['*', '+'])? == '*';
+        scanner.tag(" ")?;
+        let worry_operand = if scanner.eat("old") {
+            None
+        } else {
+            Some(scanner.number::<u64>()?)
+        };
+        scanner.end()?;
+        Ok((multiply, worry_operand, overflow))
+    }
+
+    /// Parse the divisor of a test, like `  Test: divisible by 23`, the product of the divisors
+    /// being kept in `modulus`
+    fn parse_test(line: &Line, modulus: &mut u64) -> Result<u64, ParseError> {
+        let mut scanner = line.scan();
+        scanner.tag("  Test: divisible by ")?;
+        let start = scanner.clone();
+        let divisibility = scanner.number::<u64>()?;
+        if divisibility == 0 {
+            return Err(start.error("a non-zero divisor"));
+        }
+        *modulus = modulus
+            .checked_mul(divisibility)
+            .ok_or_else(|| start.error("a divisor keeping their product in 64 bits"))?;
+        scanner.end()?;
+        Ok(divisibility)
+    }
+
+    /// Parse the monkey an item is thrown to after `prefix`, it has to be another existing one
+    fn parse_target(
+        line: &Line,
+        prefix: &str,
+        from: usize,
+        count: usize,
+    ) -> Result<usize, ParseError> {
+        let mut scanner = line.scan();
+        scanner.tag(prefix)?;
+        let start = scanner.clone();
+        let to = scanner.number()?;
+        if to == from || to >= count {
+            return Err(start.error(format!("another monkey between 0 and {}", count - 1)));
+        }
+        scanner.end()?;
+        Ok(to)
+    }
+
+    /// Monkeys as described by the notes, before any round
+    pub fn monkeys(&self) -> &[Monkey] {
+        &self.monkeys
+    }
+
+    /// Items inspected after some rounds, or the index of the monkey whose operation made a worry
+    /// level overflow. Levels cannot overflow without relief, as the parser checks their modulus
+    pub fn monkey_business(
+        &self,
+        rounds: u32,
+        divide: bool,
+        observer: &mut dyn Observer,
+    ) -> Result<Business, usize> {
+        // Make a local copy of the items as they will be thrown, the monkeys keep them in 128 bits
+        let monkeys = &self.monkeys;
+        let mut items = monkeys
+            .iter()
+            .map(|m| m.items.iter().map(|&item| u128::from(item)).collect())
+            .collect::<Vec<Vec<_>>>();
 
         // Count of inspections made by monkeys
-        let mut inspections: Vec<u64> = Vec::new();
-        inspections.resize(self.monkeys.len(), 0);
+        let mut inspections: Vec<u64> = vec![0; self.monkeys.len()];
 
         // A modulus that keeps item divisibility for every monkey
         let monkeys_modulus = self.monkeys.iter().map(|m| m.divisibility).product::<u64>();
+        let modulus = (!divide).then_some(monkeys_modulus);
 
         // Execute the rounds
-        for _ in 0..rounds {
+        for round in 1..=rounds {
             for from in 0..monkeys.len() {
-                while !monkeys[from].items.is_empty() {
-                    let old = monkeys[from].items.pop().unwrap();
-                    let (new, to) = monkeys[from].inspect_and_throw(old, divide);
-                    let new = if divide { new } else { new % monkeys_modulus };
-                    monkeys[to].items.push(new);
+                while let Some(old) = items[from].pop() {
+                    let (new, to) = monkeys[from].inspect_and_throw(old, modulus).ok_or(from)?;
+                    items[to].push(new);
                     inspections[from] += 1;
+                    observer.trace(|| {
+                        event("Inspect")
+                            .value("round", round)
+                            .value("monkey", from)
+                            .value("old", old)
+                            .value("new", new)
+                            .value("to", to)
+                    });
                 }
             }
+            if observer.step() {
+                observer.frame(&draw(round, &items, &inspections));
+            }
         }
 
-        // Get the two most active monkeys to compute the monkey business score
-        inspections.sort_unstable();
-        inspections.pop().unwrap() * inspections.pop().unwrap()
+        // Get the two most active monkeys to compute the monkey business score, the first one
+        // among monkeys inspecting as many items
+        let mut ranking = (0..inspections.len()).collect::<Vec<_>>();
+        ranking.sort_by_key(|&index| std::cmp::Reverse(inspections[index]));
+        Ok(Business {
+            inspections,
+            busiest: [ranking[0], ranking[1]],
+        })
+    }
+
+    /// Level of monkey business, or the error pointing at the operation making worry levels
+    /// overflow
+    fn level(&self, business: Result<Business, usize>) -> Result<u128, ParseError> {
+        business
+            .map(|business| business.level())
+            .map_err(|index| self.overflows[index].clone())
+    }
+}
+
+impl Solution for Puzzle {
+    type Part1 = Result<u128, ParseError>;
+    type Part2 = u128;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Self::parse_with(input, Params::default())
     }
 
-    fn part1(&self) -> u64 {
-        self.monkey_business(20, true)
+    fn part1(&self) -> Result<u128, ParseError> {
+        self.level(self.monkey_business(self.params.relief_rounds, true, &mut ()))
     }
 
-    fn part2(&self) -> u64 {
-        self.monkey_business(10000, false)
+    fn part2(&self) -> u128 {
+        self.monkey_business(self.params.worry_rounds, false, &mut ())
+            .unwrap()
+            .level()
+    }
+}
+
+impl Configure for Puzzle {
+    type Params = Params;
+
+    fn parse_with(input: &str, params: Params) -> Result<Self, ParseError> {
+        let mut issues = Issues::default();
+        let puzzle = Self::read(input, params, &mut issues);
+        issues.first(puzzle)
+    }
+}
+
+impl Lint for Puzzle {
+    fn lint(input: &str) -> Vec<ParseError> {
+        let mut issues = Issues::default();
+        Self::read(input, Params::default(), &mut issues);
+        issues.errors()
+    }
+}
+
+impl Observe for Puzzle {
+    /// An `Inspect` event for each item thrown and a frame of the items held by each monkey after
+    /// each round
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError> {
+        let puzzle = Self::from_input(input)?;
+        let (rounds, divide) = if part == 1 {
+            (puzzle.params.relief_rounds, true)
+        } else {
+            (puzzle.params.worry_rounds, false)
+        };
+        let business = puzzle.monkey_business(rounds, divide, observer);
+        Ok(puzzle.level(business)?.to_string())
+    }
+}
+
+impl Explain for Puzzle {
+    /// The two busiest monkeys and the count of items they inspected, nothing without answer
+    fn explain(&self, part: u32) -> String {
+        let business = if part == 1 {
+            self.monkey_business(self.params.relief_rounds, true, &mut ())
+        } else {
+            self.monkey_business(self.params.worry_rounds, false, &mut ())
+        };
+        let Ok(business) = business else {
+            return String::new();
+        };
+        let mut text = String::new();
+        for index in business.busiest {
+            text.push_str(&format!(
+                "monkey {index}: {} items inspected\n",
+                business.inspections[index]
+            ));
+        }
+        text
+    }
+}
+
+/// Worry levels of the items held by each monkey, along with the count of items it inspected
+fn draw(round: u32, items: &[Vec<u128>], inspections: &[u64]) -> String {
+    let mut drawing = format!("After round {round}\n");
+    for (index, (held, inspected)) in items.iter().zip(inspections).enumerate() {
+        let items = held.iter().map(u128::to_string).collect::<Vec<_>>();
+        drawing.push_str(&format!(
+            "Monkey {index} ({inspected} inspected): {}\n",
+            items.join(", ")
+        ));
     }
+    drawing
 }
 
-/// Test from puzzle input
+/// Divisors of the tests, their product keeps squared worry levels of the second part in 64 bits
+const DIVISORS: [u64; 9] = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+
+impl Generate for Puzzle {
+    /// Notes about `size` monkeys, between 2 and 9 of them
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let count = size.clamp(2, DIVISORS.len());
+        let mut divisors = DIVISORS;
+        rng.shuffle(&mut divisors);
+
+        let other = |rng: &mut Rng, index: usize| (index + rng.range(1..=count - 1)) % count;
+        let monkeys = loop {
+            let monkeys = (0..count)
+                .map(|index| {
+                    let item_count = rng.range(1..=6);
+                    let square = rng.chance(1.0 / count as f64);
+                    Monkey {
+                        items: (0..item_count).map(|_| rng.range(50..=99) as u64).collect(),
+                        multiply: square || rng.chance(0.5),
+                        worry_operand: (!square).then(|| rng.range(1..=19) as u64),
+                        divisibility: divisors[index],
+                        true_to: other(rng, index),
+                        false_to: other(rng, index),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            // Squaring worry levels quickly overflows without a modulus, retry until they fit
+            let params = Params::default();
+            let rounds = params.relief_rounds;
+            let puzzle = Puzzle {
+                monkeys,
+                overflows: Vec::new(),
+                params,
+            };
+            if puzzle.monkey_business(rounds, true, &mut ()).is_ok() {
+                break puzzle.monkeys;
+            }
+        };
+
+        let mut input = String::new();
+        for (index, monkey) in monkeys.iter().enumerate() {
+            if index != 0 {
+                input.push('\n');
+            }
+            let items = monkey.items.iter().map(u64::to_string).collect::<Vec<_>>();
+            let operator = if monkey.multiply { '*' } else { '+' };
+            let operand = monkey
+                .worry_operand
+                .map_or(String::from("old"), |operand| operand.to_string());
+            input.push_str(&format!("Monkey {index}:\n"));
+            input.push_str(&format!("  Starting items: {}\n", items.join(", ")));
+            input.push_str(&format!("  Operation: new = old {operator} {operand}\n"));
+            input.push_str(&format!("  Test: divisible by {}\n", monkey.divisibility));
+            input.push_str(&format!(
+                "    If true: throw to monkey {}\n",
+                monkey.true_to
+            ));
+            input.push_str(&format!(
+                "    If false: throw to monkey {}\n",
+                monkey.false_to
+            ));
+        }
+        input
+    }
+}
+
+impl Puzzle {
+    /// Monkey business keeping worry levels as their remainders by each divisor, in place of a
+    /// single common modulus, and inspecting items in the order they were received
+    fn remainder_business(&self, rounds: u32) -> u128 {
+        let divisors = self
+            .monkeys
+            .iter()
+            .map(|m| m.divisibility)
+            .collect::<Vec<_>>();
+        let mut items = self
+            .monkeys
+            .iter()
+            .map(|monkey| {
+                monkey
+                    .items
+                    .iter()
+                    .map(|&item| divisors.iter().map(|d| item % d).collect::<Vec<_>>())
+                    .collect::<VecDeque<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut inspections = vec![0u64; self.monkeys.len()];
+
+        for _ in 0..rounds {
+            for (from, monkey) in self.monkeys.iter().enumerate() {
+                while let Some(mut remainders) = items[from].pop_front() {
+                    for (remainder, &divisor) in remainders.iter_mut().zip(&divisors) {
+                        // Divisors may not fit in 32 bits, remainders are combined on 128 bits
+                        let val = monkey.worry_operand.map_or(*remainder, |v| v % divisor);
+                        let (old, val) = (u128::from(*remainder), u128::from(val));
+                        let new = if monkey.multiply {
+                            old * val
+                        } else {
+                            old + val
+                        };
+                        *remainder = (new % u128::from(divisor)) as u64;
+                    }
+                    let to = if remainders[from] == 0 {
+                        monkey.true_to
+                    } else {
+                        monkey.false_to
+                    };
+                    items[to].push_back(remainders);
+                    inspections[from] += 1;
+                }
+            }
+        }
+
+        inspections.sort_unstable_by(|a, b| b.cmp(a));
+        u128::from(inspections[0]) * u128::from(inspections[1])
+    }
+
+    /// Monkey business of the first part with worry levels on 128 bits
+    fn wide_business(&self, rounds: u32) -> Result<u128, ParseError> {
+        let mut items = self
+            .monkeys
+            .iter()
+            .map(|m| {
+                m.items
+                    .iter()
+                    .map(|&i| u128::from(i))
+                    .collect::<VecDeque<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut inspections = vec![0u64; self.monkeys.len()];
+
+        for _ in 0..rounds {
+            for (from, monkey) in self.monkeys.iter().enumerate() {
+                while let Some(old) = items[from].pop_front() {
+                    let val = monkey.worry_operand.map_or(old, u128::from);
+                    let new = if monkey.multiply {
+                        old.checked_mul(val)
+                    } else {
+                        old.checked_add(val)
+                    };
+                    let Some(new) = new else {
+                        return Err(self.overflows[from].clone());
+                    };
+                    let new = new / 3;
+                    let to = if new % u128::from(monkey.divisibility) == 0 {
+                        monkey.true_to
+                    } else {
+                        monkey.false_to
+                    };
+                    items[to].push_back(new);
+                    inspections[from] += 1;
+                }
+            }
+        }
+
+        inspections.sort_unstable_by(|a, b| b.cmp(a));
+        Ok(u128::from(inspections[0]) * u128::from(inspections[1]))
+    }
+}
+
+impl Reference for Puzzle {
+    fn reference_part1(&self) -> Result<u128, ParseError> {
+        self.wide_business(self.params.relief_rounds)
+    }
+
+    fn reference_part2(&self) -> u128 {
+        self.remainder_business(self.params.worry_rounds)
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s)
+    }
+}
+
+/// Test every example input against its manifest
+#[test]
+fn examples() {
+    aoc_core::testing::check_examples::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test inputs of the generator
+#[test]
+fn generated() {
+    aoc_core::testing::check_generated::<Puzzle>();
+}
+
+/// Compare with the reference solution on generated inputs
+#[test]
+fn reference() {
+    aoc_core::testing::check_reference::<Puzzle>();
+}
+
+/// Test mutated inputs never panic
 #[test]
-fn test01() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test01.txt");
-    assert_eq!(puzzle.part1(), 10605);
+fn fuzz() {
+    aoc_core::testing::check_fuzz::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test puzzle input with Windows line endings and without final newline
+#[test]
+fn crlf() {
+    let input = std::fs::read_to_string("test01.txt").unwrap();
+    let puzzle = input
+        .trim_end()
+        .replace('\n', "\r\n")
+        .parse::<Puzzle>()
+        .unwrap();
+    assert_eq!(puzzle.part1(), Ok(10605));
     assert_eq!(puzzle.part2(), 2713310158);
 }
 
-/// Test from user input
+/// Test malformed input
+#[test]
+fn malformed() {
+    let input = concat!(
+        "Monkey 0:\n",
+        "  Starting items: 79, 98\n",
+        "  Operation: new = old * 19\n",
+        "  Test: divisible by 23\n",
+        "    If true: throw to monkey 2\n",
+        "    If false: throw to monkey 1\n",
+        "\n",
+        "Monkey 1:\n",
+        "  Starting items: 54\n",
+        "  Operation: new = old + 6\n",
+        "  Test: divisible by 19\n",
+        "    If true: throw to monkey 0\n",
+        "    If false: throw to monkey 0\n",
+    );
+    let error = input.parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (5, 30));
+
+    // The product of divisors has to fit in 64 bits
+    let monkeys = |operation: &str, divisor: u64| {
+        format!(
+            concat!(
+                "Monkey 0:\n",
+                "  Starting items: 99\n",
+                "  Operation: new = old {}\n",
+                "  Test: divisible by {}\n",
+                "    If true: throw to monkey 1\n",
+                "    If false: throw to monkey 1\n",
+                "\n",
+                "Monkey 1:\n",
+                "  Starting items: 54\n",
+                "  Operation: new = old + 6\n",
+                "  Test: divisible by {}\n",
+                "    If true: throw to monkey 0\n",
+                "    If false: throw to monkey 0\n",
+            ),
+            operation, divisor, divisor
+        )
+    };
+    let error = monkeys("* 2", 1 << 40).parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (11, 22));
+}
+
+/// Test every malformed line of the notes is reported
+#[test]
+fn linted() {
+    aoc_core::testing::check_lint::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+
+    let input = concat!(
+        "Monkey 0:\n",
+        "  Starting items: 79, x\n",
+        "  Operation: new = old * 19\n",
+        "  Test: divisible by 0\n",
+        "    If true: throw to monkey 2\n",
+        "    If false: throw to monkey 1\n",
+        "\n",
+        "Monkey 1:\n",
+        "  Starting items: 54\n",
+        "  Operation: new = old / 6\n",
+        "  Test: divisible by 19\n",
+        "    If true: throw to monkey 0\n",
+    );
+    let errors = Puzzle::lint(input);
+    let positions = errors
+        .iter()
+        .map(|e| (e.line, e.column))
+        .collect::<Vec<_>>();
+    assert_eq!(positions, [(2, 23), (4, 22), (5, 30), (10, 24), (13, 1)]);
+}
+
+/// Test the items are drawn after each round and every inspection is traced
+#[test]
+fn animated() {
+    let input = std::fs::read_to_string("test01.txt").unwrap();
+    let mut recorder = aoc_core::observe::Recorder::default();
+    assert_eq!(Puzzle::observe(&input, 1, &mut recorder).unwrap(), "10605");
+    assert_eq!(recorder.frames.len(), 20);
+    assert!(recorder.frames[19].starts_with("After round 20\nMonkey 0 (101 inspected): "));
+    assert_eq!(recorder.events.len(), 101 + 95 + 7 + 105);
+    assert_eq!(
+        recorder.events[0],
+        r#"{"event":"Inspect","round":1,"monkey":0,"old":98,"new":620,"to":3}"#
+    );
+}
+
+/// Test the busiest monkeys are found
+#[test]
+fn explained() {
+    let puzzle = std::fs::read_to_string("test01.txt")
+        .unwrap()
+        .parse::<Puzzle>()
+        .unwrap();
+    let business = puzzle.monkey_business(20, true, &mut ()).unwrap();
+    assert_eq!(business.inspections, [101, 95, 7, 105]);
+    assert_eq!(business.busiest, [3, 0]);
+    assert_eq!(
+        puzzle.explain(2),
+        "monkey 0: 52166 items inspected\nmonkey 3: 52013 items inspected\n"
+    );
+}
+
+/// Test the counts of rounds can be changed
+#[test]
+fn params() {
+    let input = std::fs::read_to_string("test01.txt").unwrap();
+    let params = Params {
+        relief_rounds: 1,
+        worry_rounds: 20,
+    };
+    let puzzle = Puzzle::from_input_with(&input, params).unwrap();
+    assert_eq!(puzzle.part1(), puzzle.reference_part1());
+    assert_eq!(puzzle.part1(), Ok(4 * 5));
+    assert_eq!(puzzle.part2(), 99 * 103);
+}
+
+/// Test worry levels of the first part are kept in 128 bits, an operation making them overflow
+/// being reported as an error
+#[test]
+fn overflow() {
+    let input = concat!(
+        "Monkey 0:\n",
+        "  Starting items: 99\n",
+        "  Operation: new = old * old\n",
+        "  Test: divisible by 23\n",
+        "    If true: throw to monkey 1\n",
+        "    If false: throw to monkey 1\n",
+        "\n",
+        "Monkey 1:\n",
+        "  Starting items: 54\n",
+        "  Operation: new = old + 6\n",
+        "  Test: divisible by 19\n",
+        "    If true: throw to monkey 0\n",
+        "    If false: throw to monkey 0\n",
+    );
+
+    // Squared five times, worry levels go past 64 bits
+    let params = Params {
+        relief_rounds: 5,
+        ..Params::default()
+    };
+    let puzzle = Puzzle::from_input_with(input, params).unwrap();
+    assert_eq!(puzzle.part1(), Ok(9 * 10));
+    assert_eq!(puzzle.part1(), puzzle.reference_part1());
+
+    let puzzle = input.parse::<Puzzle>().unwrap();
+    let error = puzzle.part1().unwrap_err();
+    assert_eq!((error.line, error.column), (3, 24));
+    assert_eq!(
+        error.expected,
+        "an operation keeping worry levels in 128 bits"
+    );
+    assert_eq!(puzzle.reference_part1(), Err(error));
+    assert_eq!(puzzle.explain(1), "");
+    assert_eq!(puzzle.part2(), puzzle.reference_part2());
+}
+
+/// Test the reference keeps remainders by divisors larger than 32 bits
+#[test]
+fn wide_divisors() {
+    let input = concat!(
+        "Monkey 0:\n",
+        "  Starting items: 4294967311\n",
+        "  Operation: new = old * old\n",
+        "  Test: divisible by 4294967311\n",
+        "    If true: throw to monkey 1\n",
+        "    If false: throw to monkey 1\n",
+        "\n",
+        "Monkey 1:\n",
+        "  Starting items: 4294967310\n",
+        "  Operation: new = old * 4294967309\n",
+        "  Test: divisible by 3\n",
+        "    If true: throw to monkey 0\n",
+        "    If false: throw to monkey 0\n",
+    );
+    let puzzle = input.parse::<Puzzle>().unwrap();
+    assert_eq!(puzzle.part2(), puzzle.reference_part2());
+}
+
+/// Test the level of monkey business does not overflow with many inspections
 #[test]
-fn test02() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test02.txt");
-    assert_eq!(puzzle.part1(), 100345);
-    assert_eq!(puzzle.part2(), 28537348205);
-}
-
-fn main() {
-    let mut puzzle = Puzzle::new();
-    let input = std::env::args().nth(1).expect("No input file");
-    puzzle.configure(&input);
-    println!("{}", puzzle.part1());
-    println!("{}", puzzle.part2());
+fn level() {
+    let business = Business {
+        inspections: vec![u64::MAX, 3, u64::MAX - 1],
+        busiest: [0, 2],
+    };
+    assert_eq!(
+        business.level(),
+        u128::from(u64::MAX) * u128::from(u64::MAX - 1)
+    );
 }
@@ -2,7 +2,18 @@
 //! [Day 12: Hill Climbing Algorithm](https://adventofcode.com/2022/day/12)
 //!
 
-use std::collections::{HashSet, VecDeque};
+#![warn(missing_docs)]
+
+use aoc_core::image::{self, Rgb};
+use aoc_core::observe::{event, point};
+use aoc_core::parse::{self, Issues};
+use aoc_core::{
+    Day, Explain, Generate, Grid, Lint, Observe, Observer, ParseError, Point, Reference, Render,
+    Rng, Solution,
+};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
 
 #[derive(PartialEq)]
 enum Direction {
@@ -11,72 +22,59 @@ enum Direction {
 }
 
 struct Path {
-    position: (usize, usize),
+    position: Point,
     cost: usize,
 }
 
 struct Search {
     direction: Direction,
     paths: VecDeque<Path>,
-    visited: HashSet<(usize, usize)>,
+    /// Square each visited square was reached from, the first square being reached from itself
+    visited: HashMap<Point, Point>,
 }
 
-struct Puzzle {
-    heightmap: Vec<Vec<u32>>,
-    cols: usize,
-    rows: usize,
-    start: (usize, usize),
-    end: (usize, usize),
+/// Shortest climb to the best signal, as found by [`Puzzle::route`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Route {
+    /// Squares of the climb, from its first one to the best signal, both included
+    pub squares: Vec<Point>,
 }
 
-impl Puzzle {
-    fn new() -> Self {
-        Self {
-            heightmap: Vec::new(),
-            cols: 0,
-            rows: 0,
-            start: (0, 0),
-            end: (0, 0),
-        }
+impl Route {
+    /// Steps of the climb
+    pub fn steps(&self) -> usize {
+        self.squares.len() - 1
     }
+}
 
-    fn configure(&mut self, path: &str) {
-        let mut data = std::fs::read_to_string(path).unwrap();
-        data.pop();
-
-        self.heightmap = data
-            .split('\n')
-            .map(|l| l.chars().map(|c| c as u32).collect::<Vec<_>>())
-            .collect();
+/// Heightmap of the area with the current position and the one with the best signal
+pub struct Puzzle {
+    heightmap: Grid<u32>,
+    start: Point,
+    end: Point,
+}
 
-        self.rows = self.heightmap.len();
-        self.cols = self.heightmap[0].len();
+/// The day as run by `aor`
+pub const DAY: Day = Day::new::<Puzzle>(12, "Hill Climbing Algorithm")
+    .with_render::<Puzzle>()
+    .with_observe::<Puzzle>()
+    .with_lint::<Puzzle>()
+    .with_explain::<Puzzle>();
 
-        for (x, line) in self.heightmap.iter_mut().enumerate() {
-            for (y, c) in line.iter_mut().enumerate() {
-                if *c == 'S' as u32 {
-                    self.start = (x, y);
-                    *c = 'a' as u32;
-                } else if *c == 'E' as u32 {
-                    self.end = (x, y);
-                    *c = 'z' as u32;
-                }
-            }
-        }
-    }
-
-    fn push_path(&self, search: &mut Search, from: &Path, to: (usize, usize)) -> bool {
+impl Puzzle {
+    fn push_path(&self, search: &mut Search, from: &Path, to: Point) -> bool {
         // Check elevation
         if search.direction == Direction::Up
-            && self.heightmap[from.position.0][from.position.1] + 1 < self.heightmap[to.0][to.1]
+            && self.heightmap[from.position] + 1 < self.heightmap[to]
             || search.direction == Direction::Down
-                && self.heightmap[to.0][to.1] + 1 < self.heightmap[from.position.0][from.position.1]
+                && self.heightmap[to] + 1 < self.heightmap[from.position]
         {
             return false;
         }
 
         // Push new path if it has not been visited yet
-        if search.visited.insert(to) {
+        if let Entry::Vacant(entry) = search.visited.entry(to) {
+            entry.insert(from.position);
             search.paths.push_back(Path {
                 position: to,
                 cost: from.cost + 1,
@@ -86,13 +84,19 @@ impl Puzzle {
         true
     }
 
-    fn fewest_steps_to_end(&self, from: (usize, usize), to: &[(usize, usize)]) -> usize {
+    /// Squares of a shortest path from a square to one of `to`, in the order they are visited
+    fn shortest_path(
+        &self,
+        from: Point,
+        to: &[Point],
+        observer: &mut dyn Observer,
+    ) -> Option<Vec<Point>> {
         let mut search = Search {
             direction: Direction::Up,
             paths: VecDeque::new(),
-            visited: HashSet::new(),
+            visited: HashMap::new(),
         };
-        search.visited.insert(from);
+        search.visited.insert(from, from);
         search.paths.push_back(Path {
             position: from,
             cost: 0,
@@ -104,80 +108,453 @@ impl Puzzle {
         };
 
         // We suppose the destination is always on the same level
-        let to_level = self.heightmap[to[0].0][to[0].1];
+        let to_level = self.heightmap[to[0]];
 
-        let mut steps = usize::MAX;
-        while !search.paths.is_empty() && steps == usize::MAX {
+        let mut found = None;
+        while !search.paths.is_empty() && found.is_none() {
             let path = search.paths.pop_front().unwrap();
+            observer.trace(|| {
+                event("Visit")
+                    .raw("at", &point(path.position))
+                    .value("steps", path.cost)
+            });
 
-            let mut new_pos = Vec::new();
-            if path.position.0 + 1 < self.rows {
-                new_pos.push((path.position.0 + 1, path.position.1));
+            for pos in path.position.neighbors4() {
+                if self.heightmap.at(pos).is_some()
+                    && self.push_path(&mut search, &path, pos)
+                    && self.heightmap[pos] == to_level
+                    && to.contains(&pos)
+                {
+                    found = Some(pos);
+                }
             }
-            if path.position.0 > 0 {
-                new_pos.push((path.position.0 - 1, path.position.1));
+        }
+
+        // Walk back to the first square
+        let mut squares = vec![found?];
+        while let Some(&previous) = search.visited.get(squares.last().unwrap()) {
+            if previous == *squares.last().unwrap() {
+                break;
             }
-            if path.position.1 + 1 < self.cols {
-                new_pos.push((path.position.0, path.position.1 + 1));
+            squares.push(previous);
+        }
+        squares.reverse();
+        Some(squares)
+    }
+}
+
+impl Solution for Puzzle {
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut issues = Issues::default();
+        let puzzle = Self::read(input, &mut issues);
+        issues.first(puzzle)
+    }
+
+    fn part1(&self) -> usize {
+        self.fewest_steps(1, &mut ())
+    }
+
+    fn part2(&self) -> usize {
+        self.fewest_steps(2, &mut ())
+    }
+}
+
+impl Lint for Puzzle {
+    fn lint(input: &str) -> Vec<ParseError> {
+        let mut issues = Issues::default();
+        Self::read(input, &mut issues);
+        issues.errors()
+    }
+}
+
+impl Puzzle {
+    /// Parse the heightmap, going on after any square in error
+    fn read(input: &str, issues: &mut Issues) -> Option<Self> {
+        let (mut start, mut end) = (None, None);
+        let mut duplicates = Vec::new();
+        let elevation = |position, c| {
+            let (found, elevation, expected) = match c {
+                'a'..='z' => return Some(c as u32),
+                'S' => (&mut start, 'a', "a single start `S`"),
+                'E' => (&mut end, 'z', "a single best signal `E`"),
+                _ => return None,
+            };
+            if found.is_some() {
+                duplicates.push((position, expected));
             }
-            if path.position.1 > 0 {
-                new_pos.push((path.position.0, path.position.1 - 1));
+            found.get_or_insert(Point::from(position));
+            Some(elevation as u32)
+        };
+        let mut squares = Issues::default();
+        let heightmap = Grid::parse_checked(input, "an elevation", elevation, &mut squares);
+
+        // Duplicated positions are reported among unexpected squares, in the order of the map
+        let lines = parse::lines(input);
+        let mut errors = squares.errors();
+        errors.extend(
+            duplicates
+                .into_iter()
+                .map(|((x, y), expected)| lines[y].error(x + 1, expected)),
+        );
+        errors.sort_by_key(|error| (error.line, error.column));
+        for error in errors {
+            issues.push(error);
+        }
+        let heightmap = heightmap?;
+
+        let start =
+            issues.check(start.ok_or_else(|| parse::end_of_input(input, "the start position `S`")));
+        let end = issues
+            .check(end.ok_or_else(|| parse::end_of_input(input, "the best signal position `E`")));
+        Some(Self {
+            heightmap,
+            start: start?,
+            end: end?,
+        })
+    }
+}
+
+impl Puzzle {
+    /// Elevation of each square, from `a` to `z` as their character codes
+    pub fn heightmap(&self) -> &Grid<u32> {
+        &self.heightmap
+    }
+
+    /// Current position, marked `S` at elevation `a`
+    pub fn start(&self) -> Point {
+        self.start
+    }
+
+    /// Position with the best signal, marked `E` at elevation `z`
+    pub fn end(&self) -> Point {
+        self.end
+    }
+
+    /// Fewest steps from the start to the end in the first part, from any lowest square to the end
+    /// in the second one, searched backwards from the end. `usize::MAX` when the end cannot be
+    /// reached
+    pub fn fewest_steps(&self, part: u32, observer: &mut dyn Observer) -> usize {
+        self.route(part, observer)
+            .map_or(usize::MAX, |route| route.steps())
+    }
+
+    /// Shortest climb of a part, from the start or from any square of elevation `a` in the second
+    /// one, if the best signal can be reached
+    pub fn route(&self, part: u32, observer: &mut dyn Observer) -> Option<Route> {
+        if part == 1 {
+            let squares = self.shortest_path(self.start, &[self.end], observer)?;
+            return Some(Route { squares });
+        }
+
+        let to = self
+            .heightmap
+            .iter()
+            .filter(|(_, &height)| height == 'a' as u32)
+            .map(|(position, _)| Point::from(position))
+            .collect::<Vec<_>>();
+
+        // The search goes down from the best signal
+        let mut squares = self.shortest_path(self.end, &to, observer)?;
+        squares.reverse();
+        Some(Route { squares })
+    }
+}
+
+impl Observe for Puzzle {
+    /// A `Visit` event for each square taken out of the queue of the breadth-first search, with
+    /// the steps it took to reach it
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError> {
+        Ok(Self::from_input(input)?
+            .fewest_steps(part, observer)
+            .to_string())
+    }
+}
+
+impl Explain for Puzzle {
+    /// The squares of the climb on the heightmap, each with an arrow to the next one like in the
+    /// puzzle, `E` being the best signal
+    fn explain(&self, part: u32) -> String {
+        let Some(route) = self.route(part, &mut ()) else {
+            return String::from("the best signal cannot be reached\n");
+        };
+        let mut map = Grid::new(self.heightmap.width(), self.heightmap.height(), '.');
+        for pair in route.squares.windows(2) {
+            let step = pair[1] - pair[0];
+            map[pair[0]] = match (step.x, step.y) {
+                (1, _) => '>',
+                (-1, _) => '<',
+                (_, 1) => 'v',
+                _ => '^',
+            };
+        }
+        map[self.end] = 'E';
+        format!(
+            "{} steps from {} to {}\n{map}",
+            route.steps(),
+            route.squares[0],
+            self.end
+        )
+    }
+}
+
+impl Generate for Puzzle {
+    /// Heightmap of `size` rows, with a path from the start to the best signal position
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        // Climbing from `a` to `z` takes at least 25 steps
+        let distance = |a: Point, b: Point| a.manhattan(b) as usize;
+        let height = size;
+        let width = rng.range(2 * height..=4 * height).max(26);
+        let start = Point::from((0, rng.below(height)));
+        let end = loop {
+            let end = Point::from((rng.below(width), rng.below(height)));
+            if distance(start, end) >= 25 {
+                break end;
             }
+        };
 
-            for pos in &new_pos {
-                if self.push_path(&mut search, &path, *pos)
-                    && self.heightmap[pos.0][pos.1] == to_level
-                    && to.contains(pos)
-                {
-                    steps = path.cost + 1;
+        loop {
+            // Heights rise by one step at most from the start and from some valleys, which makes
+            // every position reachable. The valleys are kept far enough from the end to leave it
+            // on top
+            let valleys = (0..rng.range(0..=width * height / 50))
+                .map(|_| {
+                    let valley = Point::from((rng.below(width), rng.below(height)));
+                    let depth = rng.range(25usize.saturating_sub(distance(valley, end))..=25);
+                    (valley, depth)
+                })
+                .collect::<Vec<_>>();
+            let mut heightmap = Grid::from_fn(width, height, |position| {
+                let position = Point::from(position);
+                valleys
+                    .iter()
+                    .map(|&(valley, depth)| depth + distance(valley, position))
+                    .fold(distance(start, position).min(25), usize::min)
+            });
+
+            // Spikes too steep to climb make the path less direct, but they might block it
+            for _ in 0..width * height / 20 {
+                let position = Point::from((rng.below(width), rng.below(height)));
+                if position != start && position != end {
+                    heightmap[position] = (heightmap[position] + rng.range(2..=6)).min(25);
                 }
             }
+
+            let puzzle = Puzzle {
+                heightmap: Grid::from_fn(width, height, |position| {
+                    u32::from(b'a') + heightmap[position] as u32
+                }),
+                start,
+                end,
+            };
+            if puzzle.part1() == usize::MAX {
+                continue;
+            }
+
+            let map = Grid::from_fn(width, height, |position| {
+                if Point::from(position) == start {
+                    'S'
+                } else if Point::from(position) == end {
+                    'E'
+                } else {
+                    char::from(b'a' + heightmap[position] as u8)
+                }
+            });
+            return map.to_string();
         }
+    }
+}
 
-        steps
+impl Puzzle {
+    /// Steps of the shortest climb from a position to the best signal, with a plain forward search
+    fn climb(&self, from: Point) -> Option<usize> {
+        self.climb_steps(from, Some(self.end))[self.end]
     }
 
-    fn part1(&self) -> usize {
-        let to = vec![self.end];
-        self.fewest_steps_to_end(self.start, &to)
+    /// Steps of the shortest climb from a position to every other one, the search stops once
+    /// `until` is reached
+    pub fn climb_steps(&self, from: Point, until: Option<Point>) -> Grid<Option<usize>> {
+        let mut steps = Grid::new(self.heightmap.width(), self.heightmap.height(), None);
+        steps[from] = Some(0);
+        let mut queue = VecDeque::from([from]);
+        while let Some(position) = queue.pop_front() {
+            let cost = steps[position].unwrap();
+            if Some(position) == until {
+                break;
+            }
+            for next in position.neighbors4() {
+                if self.heightmap.at(next).is_none() {
+                    continue;
+                }
+                if steps[next].is_none() && self.heightmap[next] <= self.heightmap[position] + 1 {
+                    steps[next] = Some(cost + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        steps
     }
+}
 
-    fn part2(&self) -> usize {
-        let mut to = Vec::new();
-        for (i, row) in self.heightmap.iter().enumerate() {
-            for (j, val) in row.iter().enumerate() {
-                if *val == 'a' as u32 {
-                    to.push((i, j));
+impl Render for Puzzle {
+    /// Steps from the start on a scale from blue to red, positions out of reach show their
+    /// elevation in dim gray and the start and the best signal are white
+    fn render(&self) -> Grid<Rgb> {
+        let steps = self.climb_steps(self.start, None);
+        let farthest = steps
+            .iter()
+            .filter_map(|(_, &steps)| steps)
+            .max()
+            .unwrap_or(0);
+        Grid::from_fn(
+            self.heightmap.width(),
+            self.heightmap.height(),
+            |position| {
+                let point = Point::from(position);
+                if point == self.start || point == self.end {
+                    return image::WHITE;
                 }
+                match steps[position] {
+                    Some(steps) => image::gradient(steps as u64, farthest as u64),
+                    None => image::gray(u64::from(self.heightmap[position] - 'a' as u32), 50),
+                }
+            },
+        )
+    }
+}
+
+impl Reference for Puzzle {
+    fn reference_part1(&self) -> usize {
+        self.climb(self.start).unwrap_or(usize::MAX)
+    }
+
+    fn reference_part2(&self) -> usize {
+        // Climb from every lowest position in turn
+        let mut result = usize::MAX;
+        for (position, &height) in self.heightmap.iter() {
+            if height == 'a' as u32 {
+                result = result.min(self.climb(Point::from(position)).unwrap_or(usize::MAX));
             }
         }
+        result
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = ParseError;
 
-        self.fewest_steps_to_end(self.end, &to)
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s)
     }
 }
 
-/// Test from puzzle input
+/// Test every example input against its manifest
+#[test]
+fn examples() {
+    aoc_core::testing::check_examples::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test inputs of the generator
+#[test]
+fn generated() {
+    aoc_core::testing::check_generated::<Puzzle>();
+}
+
+/// Compare with the reference solution on generated inputs
+#[test]
+fn reference() {
+    aoc_core::testing::check_reference::<Puzzle>();
+}
+
+/// Test mutated inputs never panic
+#[test]
+fn fuzz() {
+    aoc_core::testing::check_fuzz::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test malformed input
+#[test]
+fn malformed() {
+    let error = "Sabqponm\nabcryxxl\naccszExk\nacctuSwj\n"
+        .parse::<Puzzle>()
+        .err()
+        .unwrap();
+    assert_eq!((error.line, error.column), (4, 6));
+}
+
+/// Test every unexpected square is reported, along with a missing or duplicated position
+#[test]
+fn linted() {
+    aoc_core::testing::check_lint::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+
+    let errors = Puzzle::lint("Sabqponm\nabcryxxl\naccsz1xk\nacctuSwj\n");
+    let positions = errors
+        .iter()
+        .map(|e| (e.line, e.column))
+        .collect::<Vec<_>>();
+    assert_eq!(positions, [(3, 6), (4, 6), (5, 1)]);
+    assert_eq!(errors[1].expected, "a single start `S`");
+
+    let errors = Puzzle::lint("SabEponm\nabc1yxxl\naccszExk\n");
+    let expected = errors
+        .iter()
+        .map(|e| (e.line, e.column, e.expected.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        expected,
+        [(2, 4, "an elevation"), (3, 6, "a single best signal `E`")]
+    );
+}
+
+/// Test the climb is traced square after square
+#[test]
+fn explained() {
+    let puzzle = std::fs::read_to_string("test01.txt")
+        .unwrap()
+        .parse::<Puzzle>()
+        .unwrap();
+    let route = puzzle.route(1, &mut ()).unwrap();
+    assert_eq!(route.steps(), 31);
+    assert_eq!(route.squares[0], Point::new(0, 0));
+    assert_eq!(route.squares[31], Point::new(5, 2));
+    assert_eq!(puzzle.route(2, &mut ()).unwrap().steps(), 29);
+    assert_eq!(
+        puzzle.explain(1),
+        concat!(
+            "31 steps from 0,0 to 5,2\n",
+            ">>vv<<<<\n",
+            "..vvv<<^\n",
+            "..vv>E^^\n",
+            "..v>>>^^\n",
+            "..>>>>>^\n",
+        )
+    );
+}
+
+/// Test the climb is drawn from the start
 #[test]
-fn test01() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test01.txt");
-    assert_eq!(puzzle.part1(), 31);
-    assert_eq!(puzzle.part2(), 29);
+fn rendered() {
+    let picture = "Sabqponm\nabcryxxl\naccszExk\nacctuvwj\nabdefghi\n"
+        .parse::<Puzzle>()
+        .unwrap()
+        .render();
+    assert_eq!((picture.width(), picture.height()), (8, 5));
+    assert_eq!(picture[(0, 0)], image::WHITE);
+    assert_eq!(picture[(5, 2)], image::WHITE);
+    assert_eq!(picture[(1, 0)], image::gradient(1, 31));
 }
 
-/// Test from user input
+/// Test the search is traced square by square, closest ones first
 #[test]
-fn test02() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test02.txt");
-    assert_eq!(puzzle.part1(), 497);
-    assert_eq!(puzzle.part2(), 492);
-}
-
-fn main() {
-    let mut puzzle = Puzzle::new();
-    let input = std::env::args().nth(1).expect("No input file");
-    puzzle.configure(&input);
-    println!("{}", puzzle.part1());
-    println!("{}", puzzle.part2());
+fn traced() {
+    let input = std::fs::read_to_string("test01.txt").unwrap();
+    let mut recorder = aoc_core::observe::Recorder::default();
+    assert_eq!(Puzzle::observe(&input, 1, &mut recorder).unwrap(), "31");
+    assert_eq!(
+        recorder.events[0],
+        r#"{"event":"Visit","at":[0,0],"steps":0}"#
+    );
+    assert!(recorder.events.last().unwrap().ends_with(r#""steps":30}"#));
 }
@@ -2,24 +2,112 @@
 //! [Day 13: Distress Signal](https://adventofcode.com/2022/day/13)
 //!
 
+#![warn(missing_docs)]
+
+use aoc_core::observe::event;
+use aoc_core::parse::{self, Issues, Line, Scanner};
+use aoc_core::{
+    Day, Explain, Generate, Lint, Observe, Observer, ParseError, Reference, Rng, Solution,
+};
 use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// Nesting levels of packets, which bounds the recursion of their parsing and comparison
+const MAX_DEPTH: usize = 100;
 
-#[derive(Clone, Copy, PartialEq)]
-enum Item {
+/// Token of a packet, lists are flattened between their start and end
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Item {
+    /// `[`
     ListStart,
+    /// `]`
     ListEnd,
+    #[allow(missing_docs)]
     Integer(u32),
 }
 
-#[derive(Clone)]
-struct Packet {
+/// Packet of the distress signal, a list of integers and lists. Packets are ordered as the
+/// puzzle compares them, so that `[1]` and `[[1]]` are equal
+#[derive(Clone, Debug)]
+pub struct Packet {
     items: Vec<Item>,
 }
 
-struct Puzzle {
+impl Packet {
+    /// Tokens of the packet, starting with the start of its outer list
+    pub fn items(&self) -> &[Item] {
+        &self.items
+    }
+}
+
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare(self, 0, other, 0)
+    }
+}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Packet {}
+
+/// A packet is parsed from a single line, like `[1,[2,3],[]]`
+impl FromStr for Packet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = parse::lines(s);
+        let line = match lines.as_slice() {
+            [line] => line,
+            [] => return Err(parse::end_of_input(s, "a packet")),
+            [_, extra, ..] => return Err(extra.error(1, "end of input")),
+        };
+        parse_packet(line)
+    }
+}
+
+/// Packets are written like in the input
+impl Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut previous = None;
+        for &item in &self.items {
+            // Items of a list are separated by commas
+            if previous.is_some_and(|previous| previous != Item::ListStart) && item != Item::ListEnd
+            {
+                write!(f, ",")?;
+            }
+            match item {
+                Item::ListStart => write!(f, "[")?,
+                Item::ListEnd => write!(f, "]")?,
+                Item::Integer(value) => write!(f, "{value}")?,
+            }
+            previous = Some(item);
+        }
+        Ok(())
+    }
+}
+
+/// Packets of the distress signal, by pairs
+pub struct Puzzle {
     packets: Vec<Packet>,
 }
 
+/// The day as run by `aor`
+pub const DAY: Day = Day::new::<Puzzle>(13, "Distress Signal")
+    .with_observe::<Puzzle>()
+    .with_lint::<Puzzle>()
+    .with_explain::<Puzzle>();
+
 fn find_list_end(packet: &Packet, start: usize) -> usize {
     let mut index = start;
     let mut level = 1;
@@ -37,181 +125,576 @@ fn find_list_end(packet: &Packet, start: usize) -> usize {
 }
 
 fn compare(first: &Packet, first_i: usize, second: &Packet, second_i: usize) -> Ordering {
-    let (left, right) = (&first.items[first_i], &second.items[second_i]);
-
-    if (left, right) == (&Item::ListStart, &Item::ListStart) {
-        return compare(first, first_i + 1, second, second_i + 1);
-    } else if (left, right) == (&Item::ListEnd, &Item::ListEnd) {
-        if first_i == first.items.len() - 1 && second_i == second.items.len() - 1 {
-            return Ordering::Equal;
-        } else if first_i == first.items.len() - 1 && second_i != second.items.len() - 1 {
+    // Walk both packets side by side, only a list compared to an integer needs a nested comparison
+    let (mut first_i, mut second_i) = (first_i, second_i);
+    loop {
+        let (left, right) = (&first.items[first_i], &second.items[second_i]);
+
+        if (left, right) == (&Item::ListStart, &Item::ListStart) {
+            (first_i, second_i) = (first_i + 1, second_i + 1);
+        } else if (left, right) == (&Item::ListEnd, &Item::ListEnd) {
+            if first_i == first.items.len() - 1 && second_i == second.items.len() - 1 {
+                return Ordering::Equal;
+            } else if first_i == first.items.len() - 1 && second_i != second.items.len() - 1 {
+                return Ordering::Less;
+            } else if first_i != first.items.len() - 1 && second_i == second.items.len() - 1 {
+                return Ordering::Greater;
+            }
+            (first_i, second_i) = (first_i + 1, second_i + 1);
+        } else if let (Item::Integer(fvalue), Item::Integer(svalue)) = (left, right) {
+            match fvalue.cmp(svalue) {
+                Ordering::Less => return Ordering::Less,
+                Ordering::Equal => (first_i, second_i) = (first_i + 1, second_i + 1),
+                Ordering::Greater => return Ordering::Greater,
+            }
+        } else if let (Item::Integer(value), Item::ListStart) = (left, right) {
+            let packet = Packet {
+                items: vec![Item::ListStart, Item::Integer(*value), Item::ListEnd],
+            };
+            let index = find_list_end(second, second_i);
+            let other = Packet {
+                items: second.items[second_i..=index].to_vec(),
+            };
+            let order = compare(&packet, 0, &other, 0);
+            if order != Ordering::Equal {
+                return order;
+            }
+            (first_i, second_i) = (first_i + 1, index + 1);
+        } else if let (Item::ListStart, Item::Integer(value)) = (left, right) {
+            let packet = Packet {
+                items: vec![Item::ListStart, Item::Integer(*value), Item::ListEnd],
+            };
+            let index = find_list_end(first, first_i);
+            let other = Packet {
+                items: first.items[first_i..=index].to_vec(),
+            };
+            let order = compare(&other, 0, &packet, 0);
+            if order != Ordering::Equal {
+                return order;
+            }
+            (first_i, second_i) = (index + 1, second_i + 1);
+        } else if let (Item::Integer(_), Item::ListEnd) = (left, right) {
+            return Ordering::Greater;
+        } else if let (Item::ListEnd, Item::Integer(_)) = (left, right) {
             return Ordering::Less;
-        } else if first_i != first.items.len() - 1 && second_i == second.items.len() - 1 {
+        } else if let (Item::ListStart, Item::ListEnd) = (left, right) {
             return Ordering::Greater;
+        } else if let (Item::ListEnd, Item::ListStart) = (left, right) {
+            return Ordering::Less;
+        } else {
+            panic!("Case not handled");
         }
-        return compare(first, first_i + 1, second, second_i + 1);
-    } else if let (Item::Integer(fvalue), Item::Integer(svalue)) = (left, right) {
-        match fvalue.cmp(svalue) {
-            Ordering::Less => return Ordering::Less,
-            Ordering::Equal => return compare(first, first_i + 1, second, second_i + 1),
-            Ordering::Greater => return Ordering::Greater,
-        }
-    } else if let (Item::Integer(value), Item::ListStart) = (left, right) {
-        let packet = Packet {
-            items: vec![Item::ListStart, Item::Integer(*value), Item::ListEnd],
-        };
-        let index = find_list_end(second, second_i);
-        let other = Packet {
-            items: second.items[second_i..=index].iter().copied().collect(),
-        };
-        let order = compare(&packet, 0, &other, 0);
-        if order == Ordering::Equal {
-            return compare(first, first_i + 1, second, index + 1);
-        }
-        return order;
-    } else if let (Item::ListStart, Item::Integer(value)) = (left, right) {
-        let packet = Packet {
-            items: vec![Item::ListStart, Item::Integer(*value), Item::ListEnd],
-        };
-        let index = find_list_end(first, first_i);
-        let other = Packet {
-            items: first.items[first_i..=index].iter().copied().collect(),
-        };
-        let order = compare(&other, 0, &packet, 0);
-        if order == Ordering::Equal {
-            return compare(first, index + 1, second, second_i + 1);
+    }
+}
+
+/// Divider packet, like `[[2]]`
+pub fn divider(value: u32) -> Packet {
+    Packet {
+        items: vec![
+            Item::ListStart,
+            Item::ListStart,
+            Item::Integer(value),
+            Item::ListEnd,
+            Item::ListEnd,
+        ],
+    }
+}
+
+/// Parse the packet written on a line
+fn parse_packet(line: &Line) -> Result<Packet, ParseError> {
+    let mut packet = Packet { items: Vec::new() };
+    let mut scanner = line.scan();
+    parse_list(&mut scanner, &mut packet.items, MAX_DEPTH)?;
+    scanner.end()?;
+    Ok(packet)
+}
+
+/// Parse a list and its content, like "[1,[2,3],[]]", nested at most `depth` levels
+fn parse_list(
+    scanner: &mut Scanner,
+    items: &mut Vec<Item>,
+    depth: usize,
+) -> Result<(), ParseError> {
+    if depth == 0 {
+        return Err(scanner.error(format!("lists nested at most {MAX_DEPTH} levels")));
+    }
+    scanner.tag("[")?;
+    items.push(Item::ListStart);
+    if !scanner.eat("]") {
+        loop {
+            match scanner.peek() {
+                Some('[') => parse_list(scanner, items, depth - 1)?,
+                Some(c) if c.is_ascii_digit() => items.push(Item::Integer(scanner.number()?)),
+                _ => return Err(scanner.error("an integer or a list")),
+            }
+            if scanner.eat("]") {
+                break;
+            }
+            if !scanner.eat(",") {
+                return Err(scanner.error("`,` or `]`"));
+            }
         }
-        return order;
-    } else if let (Item::Integer(_), Item::ListEnd) = (left, right) {
-        return Ordering::Greater;
-    } else if let (Item::ListEnd, Item::Integer(_)) = (left, right) {
-        return Ordering::Less;
-    } else if let (Item::ListStart, Item::ListEnd) = (left, right) {
-        return Ordering::Greater;
-    } else if let (Item::ListEnd, Item::ListStart) = (left, right) {
-        return Ordering::Less;
     }
+    items.push(Item::ListEnd);
+    Ok(())
+}
+
+impl Solution for Puzzle {
+    type Part1 = usize;
+    type Part2 = usize;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut issues = Issues::default();
+        let puzzle = Self::read(input, &mut issues);
+        issues.first(puzzle)
+    }
+
+    fn part1(&self) -> usize {
+        self.ordered_pairs(&mut ()).iter().sum()
+    }
+
+    fn part2(&self) -> usize {
+        let [index_two, index_six] = self.dividers(&mut ());
+        index_two * index_six
+    }
+}
 
-    panic!("Case not handled");
+impl Lint for Puzzle {
+    fn lint(input: &str) -> Vec<ParseError> {
+        let mut issues = Issues::default();
+        Self::read(input, &mut issues);
+        issues.errors()
+    }
 }
 
 impl Puzzle {
-    fn new() -> Self {
-        Self {
-            packets: Vec::new(),
-        }
-    }
-
-    fn configure(&mut self, path: &str) {
-        let mut data = std::fs::read_to_string(path).unwrap();
-        data.pop();
-
-        for couple in data.split("\n\n") {
-            for string in couple.split('\n') {
-                let mut packet = Packet { items: Vec::new() };
-                let mut integer = None;
-                for c in string.chars() {
-                    match c {
-                        '[' => packet.items.push(Item::ListStart),
-                        ']' => {
-                            if let Some(value) = integer {
-                                packet.items.push(Item::Integer(value));
-                                integer = None;
-                            }
-                            packet.items.push(Item::ListEnd);
-                        }
-                        ',' => {
-                            if let Some(value) = integer {
-                                packet.items.push(Item::Integer(value));
-                                integer = None;
-                            }
-                        }
-                        _ => {
-                            integer = if let Some(value) = integer {
-                                Some(value * 10 + c.to_digit(10).unwrap())
-                            } else {
-                                Some(c.to_digit(10).unwrap())
-                            }
-                        }
-                    }
-                }
-                self.packets.push(packet);
+    /// Parse the pairs, going on after any packet or pair in error
+    fn read(input: &str, issues: &mut Issues) -> Option<Self> {
+        let mut packets = Vec::new();
+        for couple in issues.check(parse::blocks(input))? {
+            match couple.as_slice() {
+                [_, _] => (),
+                [first] => issues.push(first.following().scan().error("a second packet")),
+                [_, _, extra, ..] => issues.push(extra.error(1, "a blank line")),
+                [] => unreachable!(),
+            }
+            for line in couple {
+                packets.extend(issues.check(parse_packet(&line)));
             }
         }
+
+        Some(Self { packets })
     }
 
-    fn part1(&self) -> usize {
-        let mut sum = 0;
+    /// Every packet, the two of a pair one after the other
+    pub fn packets(&self) -> &[Packet] {
+        &self.packets
+    }
+
+    /// Indices of the pairs in the right order, from 1, the first part adds them up
+    pub fn ordered_pairs(&self, observer: &mut dyn Observer) -> Vec<usize> {
+        let mut indices = Vec::new();
         for (index, packet) in self.packets.chunks(2).enumerate() {
             let first = packet.first().unwrap();
             let second = packet.last().unwrap();
-            if compare(first, 0, second, 0) != Ordering::Greater {
-                sum += index + 1;
+            let order = compare(first, 0, second, 0);
+            if order != Ordering::Greater {
+                indices.push(index + 1);
             }
+            observer.trace(|| {
+                event("Compare")
+                    .value("pair", index + 1)
+                    .string("order", &format!("{order:?}"))
+            });
         }
-        sum
+        indices
     }
 
-    fn part2(&self) -> usize {
-        let mut packets = self.packets.clone();
-        packets.sort_unstable_by(|a, b| compare(a, 0, b, 0));
-        let divider_two = Packet {
-            items: vec![
-                Item::ListStart,
-                Item::ListStart,
-                Item::Integer(2),
-                Item::ListEnd,
-                Item::ListEnd,
-            ],
+    /// Indices of the divider packets `[[2]]` and `[[6]]` among the sorted packets, from 1, the
+    /// decoder key of the second part is their product
+    pub fn dividers(&self, observer: &mut dyn Observer) -> [usize; 2] {
+        // Each divider comes after the packets ordered before it, and `[[6]]` after `[[2]]` too
+        let before = |divider: &Packet| {
+            self.packets
+                .iter()
+                .filter(|packet| compare(packet, 0, divider, 0) == Ordering::Less)
+                .count()
         };
-        let divider_six = Packet {
-            items: vec![
-                Item::ListStart,
-                Item::ListStart,
-                Item::Integer(6),
-                Item::ListEnd,
-                Item::ListEnd,
-            ],
+        let (index_two, index_six) = (before(&divider(2)) + 1, before(&divider(6)) + 2);
+        for (packet, index) in [("[[2]]", index_two), ("[[6]]", index_six)] {
+            observer.trace(|| {
+                event("Divider")
+                    .string("packet", packet)
+                    .value("index", index)
+            });
+        }
+        [index_two, index_six]
+    }
+}
+
+impl Explain for Puzzle {
+    /// The indices of the pairs in the right order, or of the divider packets once sorted
+    fn explain(&self, part: u32) -> String {
+        if part == 1 {
+            let indices = self.ordered_pairs(&mut ());
+            let indices = indices.iter().map(usize::to_string).collect::<Vec<_>>();
+            return format!("pairs in the right order: {}\n", indices.join(", "));
+        }
+        let [index_two, index_six] = self.dividers(&mut ());
+        format!("[[2]] is packet {index_two} and [[6]] is packet {index_six} once sorted\n")
+    }
+}
+
+impl Observe for Puzzle {
+    /// A `Compare` event with the order of each pair in the first part, a `Divider` event with
+    /// the index of each divider packet once sorted in the second one
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError> {
+        let puzzle = Self::from_input(input)?;
+        Ok(match part {
+            1 => puzzle.ordered_pairs(observer).iter().sum::<usize>(),
+            _ => puzzle.dividers(observer).iter().product(),
+        }
+        .to_string())
+    }
+}
+
+/// List of random integers and lists, nested up to 4 levels
+fn generate_list(rng: &mut Rng, depth: usize, packet: &mut String) {
+    packet.push('[');
+    for index in 0..rng.range(0..=4) {
+        if index != 0 {
+            packet.push(',');
+        }
+        if depth < 4 && rng.chance(0.3) {
+            generate_list(rng, depth + 1, packet);
+        } else {
+            packet.push_str(&rng.range(0..=10).to_string());
+        }
+    }
+    packet.push(']');
+}
+
+/// Copy of a packet with one of its integers changed, to compare packets sharing a prefix
+fn mutate(rng: &mut Rng, packet: &str) -> String {
+    let mut integers = Vec::new();
+    for (index, c) in packet.char_indices() {
+        if !c.is_ascii_digit() {
+            continue;
+        }
+        match integers.last_mut() {
+            Some((_, end)) if *end == index => *end += 1,
+            _ => integers.push((index, index + 1)),
+        }
+    }
+    if integers.is_empty() {
+        return packet.to_string();
+    }
+
+    let (start, end) = *rng.pick(&integers);
+    let value = rng.range(0..=10);
+    let replacement = match rng.below(3) {
+        0 => value.to_string(),
+        1 => format!("[{}]", &packet[start..end]),
+        _ => format!("{},{value}", &packet[start..end]),
+    };
+    format!("{}{replacement}{}", &packet[..start], &packet[end..])
+}
+
+/// Whether a packet is ordered like a divider packet, the puzzle has none so that dividers have a
+/// single possible position
+fn is_divider_like(packet: &str) -> bool {
+    let mut items = Vec::new();
+    parse_list(&mut parse::lines(packet)[0].scan(), &mut items, MAX_DEPTH).unwrap();
+    let packet = Packet { items };
+    [2, 6]
+        .into_iter()
+        .any(|value| compare(&packet, 0, &divider(value), 0) == Ordering::Equal)
+}
+
+/// New packet, or a packet close to `base` if any
+fn generate_packet(rng: &mut Rng, base: Option<&str>) -> String {
+    loop {
+        let packet = match base {
+            Some(base) => mutate(rng, base),
+            None => {
+                let mut packet = String::new();
+                generate_list(rng, 0, &mut packet);
+                packet
+            }
         };
-        let (mut index_two, mut index_six) = (0, 0);
-        for (index, packet) in packets.iter().enumerate() {
-            if compare(&divider_two, 0, packet, 0) != Ordering::Greater {
-                index_two = index + 1;
-                break;
+        if !is_divider_like(&packet) {
+            return packet;
+        }
+    }
+}
+
+impl Generate for Puzzle {
+    /// `size` pairs of packets, the second one often close to the first one
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut input = String::new();
+        for pair in 0..size {
+            if pair != 0 {
+                input.push('\n');
             }
+            let first = generate_packet(rng, None);
+            let base = rng.chance(0.5).then_some(first.as_str());
+            let second = generate_packet(rng, base);
+            let (first, second) = if rng.chance(0.5) {
+                (first, second)
+            } else {
+                (second, first)
+            };
+            input.push_str(&format!("{first}\n{second}\n"));
         }
-        for (index, packet) in packets.iter().rev().enumerate() {
-            if compare(&divider_six, 0, packet, 0) != Ordering::Less {
-                index_six = packets.len() - index + 2;
-                break;
+        input
+    }
+}
+
+/// Packet as a tree, compared by following the rules of the puzzle word for word
+#[derive(Clone, PartialEq)]
+enum Value {
+    Integer(u32),
+    List(Vec<Value>),
+}
+
+impl Value {
+    /// Build the tree of a packet from its flat items
+    fn from_items(items: &[Item]) -> Self {
+        let mut stack = vec![Vec::new()];
+        for item in items {
+            match item {
+                Item::ListStart => stack.push(Vec::new()),
+                Item::ListEnd => {
+                    let list = stack.pop().unwrap();
+                    stack.last_mut().unwrap().push(Value::List(list));
+                }
+                Item::Integer(value) => stack.last_mut().unwrap().push(Value::Integer(*value)),
             }
         }
-        index_two * index_six
+        stack.pop().unwrap().pop().unwrap()
+    }
+
+    fn compare(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Integer(left), Value::Integer(right)) => left.cmp(right),
+            (Value::List(left), Value::List(right)) => {
+                for (left, right) in left.iter().zip(right) {
+                    let order = left.compare(right);
+                    if order != Ordering::Equal {
+                        return order;
+                    }
+                }
+                left.len().cmp(&right.len())
+            }
+            (Value::Integer(_), Value::List(_)) => Value::List(vec![self.clone()]).compare(other),
+            (Value::List(_), Value::Integer(_)) => self.compare(&Value::List(vec![other.clone()])),
+        }
+    }
+}
+
+impl Reference for Puzzle {
+    fn reference_part1(&self) -> usize {
+        let values = self
+            .packets
+            .iter()
+            .map(|packet| Value::from_items(&packet.items))
+            .collect::<Vec<_>>();
+        values
+            .chunks(2)
+            .enumerate()
+            .filter(|(_, pair)| pair[0].compare(&pair[1]) != Ordering::Greater)
+            .map(|(index, _)| index + 1)
+            .sum()
+    }
+
+    fn reference_part2(&self) -> usize {
+        // Sort all the packets along with the dividers and look for them
+        let dividers =
+            [2, 6].map(|value| Value::List(vec![Value::List(vec![Value::Integer(value)])]));
+        let mut values = self
+            .packets
+            .iter()
+            .map(|packet| Value::from_items(&packet.items))
+            .chain(dividers.iter().cloned())
+            .collect::<Vec<_>>();
+        values.sort_by(Value::compare);
+        dividers
+            .iter()
+            .map(|divider| values.iter().position(|value| value == divider).unwrap() + 1)
+            .product()
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s)
     }
 }
 
-/// Test from puzzle input
+/// Test every example input against its manifest
+#[test]
+fn examples() {
+    aoc_core::testing::check_examples::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test inputs of the generator
+#[test]
+fn generated() {
+    aoc_core::testing::check_generated::<Puzzle>();
+}
+
+/// Compare with the reference solution on generated inputs
+#[test]
+fn reference() {
+    aoc_core::testing::check_reference::<Puzzle>();
+}
+
+/// Test mutated inputs never panic
+#[test]
+fn fuzz() {
+    aoc_core::testing::check_fuzz::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test packets comparison is a total order, dividers included
+#[test]
+fn properties() {
+    aoc_core::testing::check_property::<Puzzle>("total-order", |puzzle| {
+        let mut packets = puzzle.packets.clone();
+        packets.extend([divider(2), divider(6)]);
+        let order = packets
+            .iter()
+            .map(|first| {
+                packets
+                    .iter()
+                    .map(|second| compare(first, 0, second, 0))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let cmp = |i: usize, j: usize| order[i][j];
+        for i in 0..packets.len() {
+            if cmp(i, i) != Ordering::Equal {
+                return Err(format!("packet {i} is not equal to itself"));
+            }
+            for j in 0..packets.len() {
+                if cmp(i, j) != cmp(j, i).reverse() {
+                    return Err(format!("packets {i} and {j} are not antisymmetric"));
+                }
+                if cmp(i, j) == Ordering::Greater {
+                    continue;
+                }
+                for k in 0..packets.len() {
+                    if cmp(j, k) != Ordering::Greater && cmp(i, k) == Ordering::Greater {
+                        return Err(format!("packets {i}, {j} and {k} are not transitive"));
+                    }
+                }
+            }
+        }
+        Ok(())
+    });
+}
+
+/// Test puzzle input with Windows line endings and without final newline
 #[test]
-fn test01() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test01.txt");
+fn crlf() {
+    let input = std::fs::read_to_string("test01.txt").unwrap();
+    let puzzle = input
+        .trim_end()
+        .replace('\n', "\r\n")
+        .parse::<Puzzle>()
+        .unwrap();
     assert_eq!(puzzle.part1(), 13);
     assert_eq!(puzzle.part2(), 140);
 }
 
-/// Test from user input
+/// Test malformed input
 #[test]
-fn test02() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test02.txt");
-    assert_eq!(puzzle.part1(), 5393);
-    assert_eq!(puzzle.part2(), 26712);
-}
-
-fn main() {
-    let mut puzzle = Puzzle::new();
-    let input = std::env::args().nth(1).expect("No input file");
-    puzzle.configure(&input);
-    println!("{}", puzzle.part1());
-    println!("{}", puzzle.part2());
+fn malformed() {
+    let error = "[1,1,3]\n[1,[2]\n".parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (2, 7));
+
+    let input = format!("[1]\n{}{}\n", "[".repeat(101), "]".repeat(101));
+    let error = input.parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (2, 101));
+
+    let error = "[1]\n[2]\n\n\n[3]\n[4]\n".parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (4, 1));
+}
+
+/// Test every malformed packet and pair is reported
+#[test]
+fn linted() {
+    aoc_core::testing::check_lint::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+
+    let errors = Puzzle::lint("[1,1,3]\n[1,[2]\n\n[1]\n[2]\n[3]\n\n[a]\n");
+    let positions = errors
+        .iter()
+        .map(|e| (e.line, e.column))
+        .collect::<Vec<_>>();
+    assert_eq!(positions, [(2, 7), (6, 1), (9, 1), (8, 2)]);
+}
+
+/// Test the pairs in the right order and the divider packets are found, even after all packets
+#[test]
+fn explained() {
+    let puzzle = std::fs::read_to_string("test01.txt")
+        .unwrap()
+        .parse::<Puzzle>()
+        .unwrap();
+    assert_eq!(puzzle.ordered_pairs(&mut ()), [1, 2, 4, 6]);
+    assert_eq!(puzzle.dividers(&mut ()), [10, 14]);
+    assert_eq!(puzzle.explain(1), "pairs in the right order: 1, 2, 4, 6\n");
+
+    let puzzle = std::fs::read_to_string("test03.txt")
+        .unwrap()
+        .parse::<Puzzle>()
+        .unwrap();
+    assert_eq!(puzzle.dividers(&mut ()), [3, 4]);
+    assert_eq!(
+        puzzle.explain(2),
+        "[[2]] is packet 3 and [[6]] is packet 4 once sorted\n"
+    );
+}
+
+/// Test the order of each pair is traced
+#[test]
+fn traced() {
+    let input = std::fs::read_to_string("test01.txt").unwrap();
+    let mut recorder = aoc_core::observe::Recorder::default();
+    assert_eq!(Puzzle::observe(&input, 1, &mut recorder).unwrap(), "13");
+    assert_eq!(recorder.events.len(), 8);
+    assert_eq!(
+        recorder.events[1],
+        r#"{"event":"Compare","pair":2,"order":"Less"}"#
+    );
+    assert_eq!(
+        recorder.events[2],
+        r#"{"event":"Compare","pair":3,"order":"Greater"}"#
+    );
+
+    let mut recorder = aoc_core::observe::Recorder::default();
+    assert_eq!(Puzzle::observe(&input, 2, &mut recorder).unwrap(), "140");
+    assert_eq!(
+        recorder.events,
+        [
+            r#"{"event":"Divider","packet":"[[2]]","index":10}"#,
+            r#"{"event":"Divider","packet":"[[6]]","index":14}"#
+        ]
+    );
+}
+
+/// Test packets are parsed, written back and ordered on their own
+#[test]
+fn packets() {
+    let packet = |text: &str| text.parse::<Packet>().unwrap();
+    assert_eq!(packet("[1,[2,[]],3]").to_string(), "[1,[2,[]],3]");
+    assert_eq!(packet("[[]]").to_string(), "[[]]");
+    assert!(packet("[1,1,3,1,1]") < packet("[1,1,5,1,1]"));
+    assert!(packet("[[1],[2,3,4]]") < packet("[[1],4]"));
+    assert_eq!(packet("[1]"), packet("[[1]]"));
+    assert_eq!(packet("[[2]]"), divider(2));
+
+    let error = "[1,]".parse::<Packet>().unwrap_err();
+    assert_eq!((error.line, error.column), (1, 4));
 }
@@ -2,122 +2,524 @@
 //! [Day 14: Regolith Reservoir](https://adventofcode.com/2022/day/14)
 //!
 
-fn move_sand(cave: &mut Vec<Vec<char>>, sand_position: &mut (usize, usize)) -> bool {
-    let mut blocked = false;
+#![warn(missing_docs)]
 
-    if cave[sand_position.1 + 1][sand_position.0] == '.' {
-        sand_position.1 += 1;
-    } else if cave[sand_position.1 + 1][sand_position.0 - 1] == '.' {
-        sand_position.1 += 1;
-        sand_position.0 -= 1;
-    } else if cave[sand_position.1 + 1][sand_position.0 + 1] == '.' {
-        sand_position.1 += 1;
-        sand_position.0 += 1;
-    } else {
-        // Sand cannot move further
-        blocked = true;
-        cave[sand_position.1][sand_position.0] = 'o';
-        *sand_position = (500, 0);
+use aoc_core::geometry::{self, Bounds, Direction, Point};
+use aoc_core::image::Rgb;
+use aoc_core::observe::{event, point};
+use aoc_core::params::{self, Value};
+use aoc_core::parse::{self, Issues, Line};
+use aoc_core::{
+    Configure, Day, Generate, Grid, Lint, Observe, Observer, ParseError, Reference, Render, Rng,
+    Solution,
+};
+use std::str::FromStr;
+
+/// Width and height of the cave, sand never flows further
+pub const CAVE_SIZE: usize = 1000;
+
+/// Where the sand comes from
+pub const SOURCE: Point = Point::new(500, 0);
+
+/// Moves of a grain of sand, in the order they are tried
+const FALL: [Direction; 3] = [Direction::Down, Direction::DownLeft, Direction::DownRight];
+
+/// Constants of the puzzle
+#[derive(Clone, Debug)]
+pub struct Params {
+    /// Width and height of the cave, sand never flows further
+    pub cave_size: usize,
+    /// Where the sand comes from
+    pub source: Point,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            cave_size: CAVE_SIZE,
+            source: SOURCE,
+        }
     }
+}
 
-    blocked
+impl Params {
+    /// Columns of the cave on each side of the source
+    fn margin(&self) -> i64 {
+        self.source.x.min(self.cave_size as i64 - 1 - self.source.x)
+    }
+
+    /// Depth of the lowest rock, sand spreading one column per row down to the floor stays in
+    /// the cave
+    fn max_depth(&self) -> usize {
+        (self.source.y + self.margin() - 2) as usize
+    }
 }
 
-struct Puzzle {
-    cave: Vec<Vec<char>>,
+impl params::Params for Params {
+    fn fields(&mut self) -> Vec<(&'static str, &mut dyn Value)> {
+        vec![
+            ("cave_size", &mut self.cave_size),
+            ("source", &mut self.source),
+        ]
+    }
+
+    fn check(&self) -> Result<(), String> {
+        // Rocks fit between the source and the floor, which fits in the cave
+        let size = self.cave_size as i64;
+        let Point { x, y } = self.source;
+        if x < 3 || x > size - 4 || y < 0 || y + self.margin() > size - 1 {
+            return Err(format!(
+                "a source with room for the sand on its sides and below it in the {0}x{0} cave",
+                self.cave_size
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Move a unit of sand one step down the cave of rocks `#`, air `.` and sand at rest `o`. Once it
+/// cannot move further it comes to rest, which is when `true` is returned
+pub fn move_sand(cave: &mut Grid<char>, sand_position: &mut Point) -> bool {
+    for direction in FALL {
+        let next = *sand_position + direction.step();
+        if cave[next] == '.' {
+            *sand_position = next;
+            return false;
+        }
+    }
+
+    // Sand cannot move further
+    cave[*sand_position] = 'o';
+    true
+}
+
+/// Vertical slice of the cave with its rocks
+pub struct Puzzle {
+    cave: Grid<char>,
     floor: usize,
+    /// Bounds of the rocks and the source
+    area: Bounds,
+    params: Params,
+}
+
+/// The day as run by `aor`
+pub const DAY: Day = Day::new::<Puzzle>(14, "Regolith Reservoir")
+    .with_render::<Puzzle>()
+    .with_observe::<Puzzle>()
+    .with_params::<Puzzle>()
+    .with_lint::<Puzzle>();
+
+impl Solution for Puzzle {
+    type Part1 = u32;
+    type Part2 = u32;
+
+    fn parse(input: &str) -> Result<Self, ParseError> {
+        Self::parse_with(input, Params::default())
+    }
+
+    fn part1(&self) -> u32 {
+        self.flow(false, &mut ()).1
+    }
+
+    fn part2(&self) -> u32 {
+        self.flow(true, &mut ()).1
+    }
+}
+
+impl Configure for Puzzle {
+    type Params = Params;
+
+    fn parse_with(input: &str, params: Params) -> Result<Self, ParseError> {
+        let mut issues = Issues::default();
+        let puzzle = Self::read(input, params, &mut issues);
+        issues.first(puzzle)
+    }
+}
+
+impl Lint for Puzzle {
+    fn lint(input: &str) -> Vec<ParseError> {
+        let mut issues = Issues::default();
+        Self::read(input, Params::default(), &mut issues);
+        issues.errors()
+    }
 }
 
 impl Puzzle {
-    fn new() -> Self {
-        Self {
-            cave: Vec::new(),
-            floor: 2,
+    /// Parse the rock paths, going on after any path in error
+    fn read(input: &str, params: Params, issues: &mut Issues) -> Option<Self> {
+        let mut puzzle = Self {
+            cave: Grid::new(params.cave_size, params.cave_size, '.'),
+            floor: params.source.y as usize + 2,
+            area: Bounds {
+                min: params.source,
+                max: params.source,
+            },
+            params,
+        };
+        for path in parse::lines(input) {
+            issues.check(puzzle.place(&path));
         }
+        Some(puzzle)
     }
 
-    fn configure(&mut self, path: &str) {
-        let mut data = std::fs::read_to_string(path).unwrap();
-        data.pop();
+    /// Place the rocks of a path, like `498,4 -> 498,6 -> 496,6`
+    fn place(&mut self, path: &Line) -> Result<(), ParseError> {
+        let Params { cave_size, source } = self.params;
+        let max_depth = self.params.max_depth();
+        let mut scanner = path.scan();
+        let mut pos: Option<Point> = None;
+        loop {
+            let start = scanner.clone();
+            let to_x = scanner.number::<usize>()?;
+            scanner.tag(",")?;
+            let to_y = scanner.number::<usize>()?;
 
-        // Not interested in the cave size, never more than 1000
-        self.cave.resize(1000, Vec::new());
-        for row in &mut self.cave {
-            row.resize(1000, '.');
-        }
+            // Sand falls on both sides of rocks and the floor is two rows below the lowest one
+            if to_x == 0 || to_x >= cave_size - 1 {
+                return Err(start.error(format!(
+                    "a position inside the {cave_size}x{cave_size} cave"
+                )));
+            }
+            if to_y > max_depth {
+                return Err(start.error(format!("a position at most {max_depth} deep")));
+            }
 
-        // Place rocks
-        for path in data.split('\n') {
-            let mut pos: Option<(usize, usize)> = None;
-            for line in path.split(" -> ") {
-                let (x, y) = line.split_once(',').unwrap();
-                let (to_x, to_y) = (x.parse::<usize>().unwrap(), y.parse::<usize>().unwrap());
-                if let Some((from_x, from_y)) = pos {
-                    for i in from_y.min(to_y)..=from_y.max(to_y) {
-                        self.cave[i][from_x] = '#';
-                    }
-                    for i in from_x.min(to_x)..=from_x.max(to_x) {
-                        self.cave[from_y][i] = '#';
-                    }
-                }
-                pos = Some((to_x, to_y));
-                self.floor = self.floor.max(to_y + 2);
+            // A path of a single position is a single rock
+            let to = Point::from((to_x, to_y));
+            let from = pos.unwrap_or(to);
+            if from.x != to.x && from.y != to.y {
+                return Err(start.error(format!("a position in line with `{from}`")));
+            }
+            for rock in geometry::line(from, to) {
+                self.cave[rock] = '#';
+            }
+            if self.cave[source] == '#' {
+                return Err(start.error(format!("a path away from the sand source `{source}`")));
+            }
+            pos = Some(to);
+            self.floor = self.floor.max(to_y + 2);
+            self.area.include(to);
+
+            if !scanner.eat(" -> ") {
+                break;
             }
         }
+        scanner.end()
     }
+}
 
-    fn part1(&self) -> u32 {
+impl Puzzle {
+    /// Cave with its rocks `#`, the floor not included
+    pub fn cave(&self) -> &Grid<char> {
+        &self.cave
+    }
+
+    /// Row of the floor, two rows below the lowest rock
+    pub fn floor(&self) -> usize {
+        self.floor
+    }
+
+    /// Cave once the sand stopped flowing, along with the count of sand units at rest
+    pub fn flow(&self, with_floor: bool, observer: &mut dyn Observer) -> (Grid<char>, u32) {
+        let source = self.params.source;
         let mut cave = self.cave.clone();
+        let mut area = self.area;
+        if with_floor {
+            cave.row_mut(self.floor).fill('#');
+            let spread = self.floor as i64 - source.y;
+            area.include(source + Point::new(-spread, spread));
+            area.include(source + Point::new(spread, spread));
+        }
+
+        // Sand at rest on the sides of the rocks is one column further
+        area.include(area.min - Point::new(1, 0));
+        area.include(area.max + Point::new(1, 0));
+
         let mut sand_count = 0;
-        let mut sand_position = (500, 0);
-        while sand_position.1 < 999 {
+        let mut sand_position = source;
+
+        // Without a floor, sand falls out of the cave unless rocks all around the source keep it
+        let bottom = cave.height() as i64 - 1;
+        while sand_position.y < bottom && cave[source] != 'o' {
+            // Sand comes to rest where it was before trying to move
+            let resting = sand_position;
             if move_sand(&mut cave, &mut sand_position) {
+                sand_position = source;
                 sand_count += 1;
+                observer.trace(|| {
+                    event("Rest")
+                        .value("count", sand_count)
+                        .raw("at", &point(resting))
+                });
+                if observer.step() {
+                    observer.frame(&draw(&cave, area));
+                }
             }
         }
-        sand_count
+        (cave, sand_count)
     }
+}
 
-    fn part2(&self) -> u32 {
-        let mut cave = self.cave.clone();
+impl Observe for Puzzle {
+    /// A `Rest` event and a frame of the cave each time a unit of sand comes to rest
+    fn observe(input: &str, part: u32, observer: &mut dyn Observer) -> Result<String, ParseError> {
+        Ok(Self::from_input(input)?
+            .flow(part == 2, observer)
+            .1
+            .to_string())
+    }
+}
 
-        for x in 0..1000 {
-            cave[self.floor][x] = '#';
+/// Drawing of an area of the cave
+fn draw(cave: &Grid<char>, area: Bounds) -> String {
+    let mut drawing = String::new();
+    for y in area.min.y..=area.max.y {
+        drawing.extend((area.min.x..=area.max.x).map(|x| cave[Point::new(x, y)]));
+        drawing.push('\n');
+    }
+    drawing
+}
+
+impl Render for Puzzle {
+    /// Rocks and the floor in gray, sand at rest in the first part in light yellow and sand only
+    /// added by the floor of the second part in darker orange
+    fn render(&self) -> Grid<Rgb> {
+        let (first, _) = self.flow(false, &mut ());
+        let (second, _) = self.flow(true, &mut ());
+
+        // The floor spans the whole cave, the picture is cropped to the rocks and sand above it
+        let bounds = Bounds::of(
+            second
+                .iter()
+                .filter(|&((_, y), &c)| c != '.' && y != self.floor)
+                .map(|(position, _)| Point::from(position)),
+        )
+        .expect("sand at rest below the source");
+        let left = bounds.min.x - 1;
+        Grid::from_fn(bounds.width() as usize + 2, self.floor + 1, |position| {
+            let point = Point::from(position) + Point::new(left, 0);
+            match (first[point], second[point]) {
+                ('o', _) => [240, 210, 110],
+                (_, 'o') => [200, 130, 50],
+                (_, '#') => [110, 105, 100],
+                _ => [25, 25, 35],
+            }
+        })
+    }
+}
+
+impl Generate for Puzzle {
+    /// `size` rock paths below the sand source, in a cave large enough for the sand to pile up
+    fn generate(rng: &mut Rng, size: usize) -> String {
+        let mut input = String::new();
+        for _ in 0..size {
+            // Deeper caves take longer to fill, their depth grows along with the count of paths
+            let depth = (25 + 5 * size).min(150);
+            let (mut x, mut y) = (rng.range(400..=600), rng.range(25..=depth));
+            let mut path = format!("{x},{y}");
+            let mut horizontal = rng.chance(0.5);
+            for _ in 0..rng.range(0..=4) {
+                let length = rng.range(1..=10);
+                let position = if horizontal { &mut x } else { &mut y };
+                if rng.chance(0.5) {
+                    *position += length;
+                } else {
+                    *position -= length;
+                }
+                path.push_str(&format!(" -> {x},{y}"));
+                horizontal = !horizontal;
+            }
+            input.push_str(&path);
+            input.push('\n');
         }
+        input
+    }
+}
 
-        let mut sand_count = 0;
-        let mut sand_position = (500, 0);
-        while cave[0][500] != 'o' {
-            if move_sand(&mut cave, &mut sand_position) {
-                sand_count += 1;
+impl Puzzle {
+    /// Pour sand grain by grain on the rocks, until it falls below them or, with a floor, until it
+    /// blocks the source
+    fn pour(&self, with_floor: bool) -> u32 {
+        let rocks = self
+            .cave
+            .iter()
+            .filter(|(_, &c)| c == '#')
+            .map(|(position, _)| Point::from(position))
+            .collect::<Vec<_>>();
+        let source = self.params.source;
+        let lowest = Bounds::of(rocks.iter().copied())
+            .map_or(0, |bounds| bounds.max.y)
+            .max(source.y);
+        let floor = lowest + 2;
+
+        // Sand spreads by at most one column per row, so the floor bounds its width. Points are
+        // taken from the left of the floor below the source
+        let left = Point::new(source.x - floor, 0);
+        let mut occupied = Grid::new(2 * floor as usize + 1, floor as usize, false);
+        for &rock in &rocks {
+            if let Some(cell) = occupied.at_mut(rock - left) {
+                *cell = true;
+            }
+        }
+
+        let mut count = 0;
+        while !occupied[source - left] {
+            let mut sand = source - left;
+            'fall: loop {
+                if !with_floor && sand.y > lowest {
+                    return count;
+                }
+                for direction in FALL {
+                    let next = sand + direction.step();
+                    if next.y < floor && !occupied[next] {
+                        sand = next;
+                        continue 'fall;
+                    }
+                }
+                break;
             }
+            occupied[sand] = true;
+            count += 1;
         }
-        sand_count
+        count
+    }
+}
+
+impl Reference for Puzzle {
+    fn reference_part1(&self) -> u32 {
+        self.pour(false)
+    }
+
+    fn reference_part2(&self) -> u32 {
+        self.pour(true)
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_input(s)
     }
 }
 
-/// Test from puzzle input
+/// Test every example input against its manifest
+#[test]
+fn examples() {
+    aoc_core::testing::check_examples::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test inputs of the generator
 #[test]
-fn test01() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test01.txt");
-    assert_eq!(puzzle.part1(), 24);
-    assert_eq!(puzzle.part2(), 93);
+fn generated() {
+    aoc_core::testing::check_generated::<Puzzle>();
 }
 
-/// Test from user input
+/// Compare with the reference solution on generated inputs
 #[test]
-fn test02() {
-    let mut puzzle = Puzzle::new();
-    puzzle.configure("test02.txt");
-    assert_eq!(puzzle.part1(), 961);
-    assert_eq!(puzzle.part2(), 26375);
-}
-
-fn main() {
-    let mut puzzle = Puzzle::new();
-    let input = std::env::args().nth(1).expect("No input file");
-    puzzle.configure(&input);
-    println!("{}", puzzle.part1());
-    println!("{}", puzzle.part2());
+fn reference() {
+    aoc_core::testing::check_reference::<Puzzle>();
+}
+
+/// Test mutated inputs never panic
+#[test]
+fn fuzz() {
+    aoc_core::testing::check_fuzz::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+}
+
+/// Test malformed input
+#[test]
+fn malformed() {
+    let error = "498,4 -> 498,6 -> 496,7\n".parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (1, 19));
+
+    let error = "498,4 -> 498,600\n".parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (1, 10));
+
+    let error = "500,2 -> 500,0\n".parse::<Puzzle>().err().unwrap();
+    assert_eq!((error.line, error.column), (1, 10));
+}
+
+/// Test every malformed path is reported
+#[test]
+fn linted() {
+    aoc_core::testing::check_lint::<Puzzle>(env!("CARGO_MANIFEST_DIR"));
+
+    let errors =
+        Puzzle::lint("498,4 -> 498,6 -> 496,7\n503,4 -> 502,4\n0,9 -> 494,9\n498,4 498,6\n");
+    let positions = errors
+        .iter()
+        .map(|e| (e.line, e.column))
+        .collect::<Vec<_>>();
+    assert_eq!(positions, [(1, 19), (3, 1), (4, 6)]);
+}
+
+/// Test rocks closing the sand source
+#[test]
+fn enclosed() {
+    let puzzle = "499,0 -> 499,1 -> 501,1 -> 501,0\n"
+        .parse::<Puzzle>()
+        .unwrap();
+    assert_eq!(puzzle.part1(), 1);
+    assert_eq!(puzzle.part2(), 1);
+    assert_eq!(puzzle.reference_part1(), 1);
+}
+
+/// Test a path of a single position is a single rock
+#[test]
+fn single() {
+    let puzzle = "498,10\n".parse::<Puzzle>().unwrap();
+    assert_eq!(puzzle.part1(), 0);
+    assert_eq!(puzzle.part2(), 143);
+    assert_eq!(puzzle.reference_part1(), 0);
+    assert_eq!(puzzle.reference_part2(), 143);
+    assert!("500,0\n".parse::<Puzzle>().is_err());
+}
+
+/// Test the picture is cropped around the sand
+#[test]
+fn rendered() {
+    let puzzle = "498,4 -> 498,6 -> 496,6\n503,4 -> 502,4 -> 502,9 -> 494,9\n"
+        .parse::<Puzzle>()
+        .unwrap();
+    let picture = puzzle.render();
+    assert_eq!((picture.width(), picture.height()), (23, 12));
+    assert_eq!(picture[(11, 11)], [110, 105, 100]);
+    assert_eq!(picture[(11, 0)], [200, 130, 50]);
+    assert_eq!(picture[(11, 2)], [240, 210, 110]);
+}
+
+/// Test the sand is drawn and traced each time it comes to rest
+#[test]
+fn animated() {
+    let mut recorder = aoc_core::observe::Recorder::default();
+    assert_eq!(
+        Puzzle::observe("499,2 -> 501,2\n", 1, &mut recorder).unwrap(),
+        "1"
+    );
+    assert_eq!(recorder.frames, [".....\n..o..\n.###.\n"]);
+    assert_eq!(
+        recorder.events,
+        [r#"{"event":"Rest","count":1,"at":[500,1]}"#]
+    );
+}
+
+/// Test the size of the cave and the source of the sand can be changed
+#[test]
+fn params() {
+    let input = std::fs::read_to_string("test01.txt").unwrap();
+    let params = Params {
+        cave_size: 600,
+        source: Point::new(500, 3),
+    };
+    let puzzle = Puzzle::from_input_with(&input, params.clone()).unwrap();
+    assert_eq!(puzzle.part1(), puzzle.reference_part1());
+    assert_eq!(puzzle.part2(), puzzle.reference_part2());
+    assert_eq!((puzzle.part1(), puzzle.part2()), (19, 19));
+
+    // The floor has to stay in the cave
+    let error = Puzzle::from_input_with("500,101 -> 501,101\n", params)
+        .err()
+        .unwrap();
+    assert_eq!((error.line, error.column), (1, 1));
 }